@@ -0,0 +1,218 @@
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+use crate::arm::{Arm, Joint};
+use crate::geometry::Rectangle;
+use crate::klapi::Klapi;
+use crate::GameObject;
+
+/// Lowest release angle the player can aim at, in degrees.
+pub const MIN_AIM_ANGLE: f32 = 0.0;
+/// Highest release angle the player can aim at, in degrees.
+pub const MAX_AIM_ANGLE: f32 = 85.0;
+/// Seconds the power meter takes to rise from empty to full and back.
+pub const POWER_METER_PERIOD: f32 = 1.6;
+/// Angular acceleration of the swing at no power, in degrees per second².
+pub const MIN_LAUNCH_ACCELERATION: f32 = 240.0;
+/// Angular acceleration of the swing at full power.
+pub const MAX_LAUNCH_ACCELERATION: f32 = 1200.0;
+/// Share of the full reach the klapi is flung from at no power. Full power
+/// reaches out with the whole forearm.
+pub const MIN_RADIUS_SCALE: f32 = 0.8;
+/// Length of a drag, in world units, that throws at full power.
+pub const MAX_DRAG_LENGTH: f32 = 1.5;
+/// Launch speed of the klapi at full drag, in world units per second.
+pub const MAX_DRAG_SPEED: f32 = 9.0;
+/// Drags shorter than this are let go without throwing.
+pub const MIN_DRAG_LENGTH: f32 = 0.1;
+/// Spin a klapi can be let go with at most, in degrees per second.
+pub const MAX_SPIN: f32 = 720.0;
+
+/// Meter that rises and falls between 0.0 and 1.0 until the player locks it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PowerMeter {
+    pub time: f32,
+}
+
+impl PowerMeter {
+    pub fn value(&self) -> f32 {
+        let phase = (self.time / POWER_METER_PERIOD).fract();
+        return 1.0 - (1.0 - 2.0 * phase).abs();
+    }
+}
+
+impl GameObject<PowerMeter> for PowerMeter {
+    fn update(&self, delta: f32) -> PowerMeter {
+        return PowerMeter {
+            time: self.time + delta,
+        };
+    }
+}
+
+/// Acceleration of the swing for a locked `power`.
+pub fn launch_acceleration(power: f32) -> f32 {
+    return MIN_LAUNCH_ACCELERATION + (MAX_LAUNCH_ACCELERATION - MIN_LAUNCH_ACCELERATION) * power;
+}
+
+/// The arm set up to swing with `power` and release at `aim_angle`. The
+/// swing stops at its maximum angle, where the klapi is let go. The elbow is
+/// held straight, so the klapi leaves in the direction aimed at, and the
+/// forearm is shortened at low power.
+pub fn aim_arm(arm: &Arm, aim_angle: f32, power: f32) -> Arm {
+    let scale = MIN_RADIUS_SCALE + (1.0 - MIN_RADIUS_SCALE) * power.clamp(0.0, 1.0);
+    let forearm_length = arm.reach() * scale - arm.upper_arm_length;
+    return Arm {
+        shoulder: Joint {
+            max_angle: aim_angle.clamp(MIN_AIM_ANGLE, MAX_AIM_ANGLE),
+            ..arm.shoulder
+        },
+        elbow: Joint::locked(0.0),
+        forearm_length,
+        forearm_rect: Rectangle {
+            height: forearm_length,
+            ..arm.forearm_rect.clone()
+        },
+        ..arm.clone()
+    }
+    .posed(arm.shoulder.angle);
+}
+
+/// Share of full power a drag pulled back by `pull` throws with.
+pub fn drag_power(pull: Vec2) -> f32 {
+    return (pull.length() / MAX_DRAG_LENGTH).min(1.0);
+}
+
+/// The arm posed to release in the direction of `pull`, like a slingshot,
+/// with the elbow straight and swinging fast enough to launch the klapi at a
+/// speed set by its length.
+pub fn drag_arm(arm: &Arm, pull: Vec2) -> Arm {
+    let angle = pull.y.atan2(pull.x).to_degrees();
+    let launch_speed = drag_power(pull) * MAX_DRAG_SPEED;
+    // The inverse of the tangential velocity of the hand.
+    let speed = launch_speed * 360.0 / (2.0 * PI * arm.reach());
+    let straight = Arm {
+        elbow: Joint::locked(0.0),
+        ..arm.clone()
+    };
+    let posed = straight.posed(angle.clamp(MIN_AIM_ANGLE, MAX_AIM_ANGLE));
+    return Arm {
+        shoulder: Joint {
+            speed,
+            acceleration: 0.0,
+            ..posed.shoulder
+        },
+        ..posed
+    };
+}
+
+/// The klapi let go with `topspin`, from -1.0 for full backspin to 1.0 for
+/// full topspin. Throws go to the right, so topspin turns the klapi
+/// clockwise. It then skids on when it lands, while backspin checks it up.
+pub fn spin_klapi(klapi: &Klapi, topspin: f32) -> Klapi {
+    return Klapi {
+        rotational_speed: klapi.rotational_speed - topspin.clamp(-1.0, 1.0) * MAX_SPIN,
+        ..klapi.clone()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+    use crate::arm::new_arm;
+    use crate::barrier::tests::test_barrier;
+    use crate::klapi::tests::test_klapi;
+
+    #[test]
+    fn test_power_meter_oscillates() {
+        let meter = PowerMeter::default();
+        assert_eq!(meter.value(), 0.0);
+        let half = meter.update(POWER_METER_PERIOD * 0.5);
+        assert!((half.value() - 1.0).abs() < 1e-5);
+        let quarter = meter.update(POWER_METER_PERIOD * 0.25);
+        let three_quarters = meter.update(POWER_METER_PERIOD * 0.75);
+        assert!((quarter.value() - 0.5).abs() < 1e-5);
+        assert!((three_quarters.value() - 0.5).abs() < 1e-5);
+    }
+
+    /// Swings the arm from its lowest angle until it stops at the aim.
+    fn swing(arm: &Arm, power: f32) -> Arm {
+        let mut arm = arm
+            .posed(arm.shoulder.min_angle)
+            .swing(launch_acceleration(power));
+        while arm.shoulder.angle < arm.shoulder.max_angle {
+            arm = arm.update(1.0 / 120.0);
+        }
+        return arm;
+    }
+
+    #[test]
+    fn test_aimed_release() {
+        let arm = new_arm(vec2(0.0, 0.0));
+        let weak = swing(&aim_arm(&arm, 30.0, 0.2), 0.2);
+        let strong = swing(&aim_arm(&arm, 30.0, 0.9), 0.9);
+        assert_eq!(weak.shoulder.angle, 30.0);
+        assert_eq!(strong.shoulder.angle, 30.0);
+        let weak_klapi = weak.launch_klapi();
+        let strong_klapi = strong.launch_klapi();
+        assert!(strong_klapi.speed.length() > weak_klapi.speed.length());
+        let direction = strong_klapi.speed.normalize();
+        assert!((direction.y.atan2(direction.x).to_degrees() - 30.0).abs() < 1e-3);
+        assert_eq!(aim_arm(&arm, 120.0, 1.0).shoulder.max_angle, MAX_AIM_ANGLE);
+    }
+
+    #[test]
+    fn test_power_reaches_out() {
+        let arm = new_arm(vec2(0.0, 0.0));
+        let reach = |power: f32| {
+            let aimed = aim_arm(&arm, 30.0, power);
+            return aimed.hand_position().distance(aimed.pivot_location);
+        };
+        assert!((reach(0.0) - arm.reach() * MIN_RADIUS_SCALE).abs() < 1e-5);
+        assert!((reach(1.0) - arm.reach()).abs() < 1e-5);
+        assert!(reach(0.5) > reach(0.0));
+        let weak = swing(&aim_arm(&arm, 30.0, 0.0), 0.5).launch_klapi();
+        let strong = swing(&aim_arm(&arm, 30.0, 1.0), 0.5).launch_klapi();
+        assert!(strong.speed.length() > weak.speed.length());
+    }
+
+    #[test]
+    fn test_drag_arm() {
+        let arm = new_arm(vec2(0.0, 0.0));
+        let pull = vec2(1.0, 1.0).normalize() * MAX_DRAG_LENGTH * 0.5;
+        let dragged = drag_arm(&arm, pull);
+        assert!((dragged.shoulder.angle - 45.0).abs() < 1e-4);
+        let klapi = dragged.launch_klapi();
+        assert!((klapi.speed.length() - MAX_DRAG_SPEED * 0.5).abs() < 1e-4);
+        assert!((klapi.speed.normalize() - pull.normalize()).length() < 1e-4);
+
+        let too_far = drag_arm(&arm, vec2(-10.0, 0.0));
+        assert_eq!(too_far.shoulder.angle, MAX_AIM_ANGLE);
+        assert_eq!(drag_power(vec2(-10.0, 0.0)), 1.0);
+    }
+
+    #[test]
+    fn test_spin_changes_bounce() {
+        let mut floor = test_barrier(vec2(0.0, -1.0), 20.0, 0.2);
+        floor.material.static_friction = 0.5;
+        floor.material.dynamic_friction = 0.5;
+        let bounce = |topspin: f32| {
+            let klapi = spin_klapi(&test_klapi(vec2(0.0, -0.85), vec2(2.0, -3.0)), topspin);
+            let contact = floor.rect.contact(&klapi.rect).unwrap();
+            floor.on_collision(&klapi, &contact)
+        };
+        let flat = bounce(0.0);
+        let topspin = bounce(1.0);
+        let backspin = bounce(-1.0);
+        assert_eq!(
+            spin_klapi(&test_klapi(Vec2::ZERO, Vec2::ZERO), 2.0).rotational_speed,
+            -MAX_SPIN
+        );
+        assert!(topspin.speed.x > flat.speed.x);
+        assert!(backspin.speed.x < flat.speed.x);
+        assert!(backspin.speed.x < 0.0);
+    }
+}
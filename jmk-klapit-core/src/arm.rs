@@ -0,0 +1,383 @@
+use glam::{vec2, Vec2};
+
+use crate::geometry::Rectangle;
+use crate::klapi::Klapi;
+use crate::projectile::Projectile;
+use crate::GameObject;
+
+/// Share of the shoulder's acceleration the elbow straightens with during a
+/// swing. The forearm lags behind and whips straight about when the shoulder
+/// passes 45 degrees.
+pub const ELBOW_WHIP_RATIO: f32 = 0.75;
+/// Degrees per second the elbow bends back while the arm winds up.
+pub const ELBOW_WIND_UP_SPEED: f32 = -120.0;
+
+/// A hinge of the arm. Angles are in degrees, counterclockwise, and the
+/// speed and acceleration in degrees per second and per second².
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Joint {
+    pub angle: f32,
+    pub min_angle: f32,
+    pub max_angle: f32,
+    pub speed: f32,
+    pub acceleration: f32,
+}
+
+impl Joint {
+    /// A joint at rest at `angle`.
+    pub fn new(angle: f32, min_angle: f32, max_angle: f32) -> Joint {
+        Joint {
+            angle,
+            min_angle,
+            max_angle,
+            speed: 0.0,
+            acceleration: 0.0,
+        }
+    }
+
+    /// Speed the joint actually turns at. A joint held at one of its limits
+    /// does not turn, however hard it is driven.
+    pub fn turning_speed(&self) -> f32 {
+        let pinned = (self.angle >= self.max_angle && self.speed > 0.0)
+            || (self.angle <= self.min_angle && self.speed < 0.0);
+        return if pinned { 0.0 } else { self.speed };
+    }
+
+    /// The joint held still at `angle`, which becomes both of its limits.
+    pub fn locked(angle: f32) -> Joint {
+        return Joint::new(angle, angle, angle);
+    }
+}
+
+impl GameObject<Joint> for Joint {
+    /// Stops at the limits, but keeps the speed it was driven with, as the
+    /// shoulder lets go of the klapi at its maximum angle.
+    fn update(&self, delta: f32) -> Joint {
+        let speed = self.speed + self.acceleration * delta;
+        let angle = (self.angle + speed * delta).clamp(self.min_angle, self.max_angle);
+        return Joint {
+            angle,
+            speed,
+            ..*self
+        };
+    }
+}
+
+/// Direction a segment hanging from a joint at `angle` points in. At 0.0 it
+/// hangs straight down.
+fn segment_direction(angle: f32) -> Vec2 {
+    let theta = angle.to_radians();
+    return vec2(theta.sin(), -theta.cos());
+}
+
+/// Velocity of a point at `offset` from a joint turning at `speed`.
+fn tangential_velocity(offset: Vec2, speed: f32) -> Vec2 {
+    return offset.perp() * speed.to_radians();
+}
+
+/// The throwing arm: an upper arm turning about the shoulder at
+/// `pivot_location` and a forearm, ending in the hand, turning about the
+/// elbow. The elbow angle is relative to the upper arm, 0.0 being straight.
+/// The hand holds `projectile` until it is thrown.
+#[derive(Clone)]
+pub struct Arm {
+    pub upper_arm_rect: Rectangle,
+    pub forearm_rect: Rectangle,
+    pub hand_rect: Rectangle,
+    pub shoulder: Joint,
+    pub elbow: Joint,
+    pub upper_arm_length: f32,
+    pub forearm_length: f32,
+    pub thrown: bool,
+    pub start_location: Vec2,
+    pub pivot_location: Vec2,
+    pub projectile: Projectile,
+}
+
+impl Arm {
+    /// Moves the segments and the hand to the angles of the joints.
+    fn with_segments(&self) -> Arm {
+        let elbow = self.elbow_position();
+        let forearm_angle = self.forearm_angle();
+        return Arm {
+            upper_arm_rect: Rectangle {
+                position: self.pivot_location
+                    + segment_direction(self.shoulder.angle) * self.upper_arm_length * 0.5,
+                rotation: self.shoulder.angle,
+                ..self.upper_arm_rect.clone()
+            },
+            forearm_rect: Rectangle {
+                position: elbow + segment_direction(forearm_angle) * self.forearm_length * 0.5,
+                rotation: forearm_angle,
+                ..self.forearm_rect.clone()
+            },
+            hand_rect: Rectangle {
+                position: self.hand_position(),
+                rotation: forearm_angle,
+                ..self.hand_rect.clone()
+            },
+            ..self.clone()
+        };
+    }
+
+    /// Angle the forearm points at, measured like the shoulder angle.
+    pub fn forearm_angle(&self) -> f32 {
+        return self.shoulder.angle + self.elbow.angle;
+    }
+
+    /// Distance from the shoulder to the hand with the elbow straight.
+    pub fn reach(&self) -> f32 {
+        return self.upper_arm_length + self.forearm_length;
+    }
+
+    pub fn elbow_position(&self) -> Vec2 {
+        return self.pivot_location
+            + segment_direction(self.shoulder.angle) * self.upper_arm_length;
+    }
+
+    /// End of the forearm, where the hand grips the klapi.
+    pub fn hand_position(&self) -> Vec2 {
+        return self.elbow_position()
+            + segment_direction(self.forearm_angle()) * self.forearm_length;
+    }
+
+    /// Velocity of the hand: the swing of the whole arm about the shoulder
+    /// plus the forearm turning about the elbow.
+    pub fn hand_velocity(&self) -> Vec2 {
+        let upper_arm = self.elbow_position() - self.pivot_location;
+        let forearm = self.hand_position() - self.elbow_position();
+        return tangential_velocity(upper_arm, self.shoulder.speed)
+            + tangential_velocity(forearm, self.forearm_speed());
+    }
+
+    /// Speed the forearm, and the hand with it, turns at: the swing of the
+    /// shoulder plus the elbow turning.
+    pub fn forearm_speed(&self) -> f32 {
+        return self.shoulder.speed + self.elbow.turning_speed();
+    }
+
+    /// Pose between `self` and the next physics state `next`, for drawing.
+    pub fn lerp(&self, next: &Arm, alpha: f32) -> Arm {
+        let lerp_angle = |from: f32, to: f32| from + (to - from) * alpha;
+        return Arm {
+            shoulder: Joint {
+                angle: lerp_angle(self.shoulder.angle, next.shoulder.angle),
+                ..next.shoulder
+            },
+            elbow: Joint {
+                angle: lerp_angle(self.elbow.angle, next.elbow.angle),
+                ..next.elbow
+            },
+            ..next.clone()
+        }
+        .with_segments();
+    }
+
+    /// The arm turned about its shoulder to `angle`.
+    pub fn posed(&self, angle: f32) -> Arm {
+        return Arm {
+            shoulder: Joint {
+                angle,
+                ..self.shoulder
+            },
+            ..self.clone()
+        }
+        .with_segments();
+    }
+
+    /// The arm turning back at `speed` about the shoulder, bending the
+    /// elbow back as it goes.
+    pub fn wind_up(&self, speed: f32) -> Arm {
+        return Arm {
+            shoulder: Joint {
+                speed,
+                acceleration: 0.0,
+                ..self.shoulder
+            },
+            elbow: Joint {
+                speed: ELBOW_WIND_UP_SPEED,
+                acceleration: 0.0,
+                ..self.elbow
+            },
+            ..self.clone()
+        };
+    }
+
+    /// The arm starting to swing forward from rest with `acceleration`. The
+    /// elbow follows, straightening more slowly.
+    pub fn swing(&self, acceleration: f32) -> Arm {
+        return Arm {
+            shoulder: Joint {
+                speed: 0.0,
+                acceleration,
+                ..self.shoulder
+            },
+            elbow: Joint {
+                speed: 0.0,
+                acceleration: acceleration * ELBOW_WHIP_RATIO,
+                ..self.elbow
+            },
+            ..self.clone()
+        };
+    }
+
+    /// The arm with `projectile` in its hand.
+    pub fn holding(&self, projectile: Projectile) -> Arm {
+        return Arm {
+            projectile,
+            ..self.clone()
+        };
+    }
+
+    /// The projectile as held in the hand, crosswise to the forearm. Letting
+    /// go of it keeps the pose, the velocity and the turning of the hand, so
+    /// it leaves the hand without a jump.
+    pub fn launch_klapi(&self) -> Klapi {
+        let projectile = &self.projectile;
+        Klapi {
+            rect: Rectangle {
+                position: self.hand_rect.position,
+                height: projectile.height,
+                width: projectile.width,
+                rotation: self.hand_rect.rotation,
+            },
+            speed: self.hand_velocity(),
+            rotational_speed: self.forearm_speed(),
+            max_speed: projectile.max_speed,
+            mass: projectile.mass,
+            linear_drag: projectile.linear_drag,
+            quadratic_drag: projectile.quadratic_drag,
+            material: projectile.material.clone(),
+            forces: vec![vec2(0.0, -9.81 * projectile.mass)],
+        }
+    }
+}
+
+impl GameObject<Arm> for Arm {
+    fn update(&self, delta: f32) -> Arm {
+        return Arm {
+            shoulder: self.shoulder.update(delta),
+            elbow: self.elbow.update(delta),
+            ..self.clone()
+        }
+        .with_segments();
+    }
+}
+
+pub fn new_arm(start_location: Vec2) -> Arm {
+    let height = 0.9;
+    let width = 0.18;
+    let angle = 0.0;
+    let pivot_y = start_location.y + height * 0.5;
+    let pivot_location = vec2(start_location.x, pivot_y);
+    let segment = Rectangle {
+        height: height * 0.5,
+        width,
+        position: start_location,
+        rotation: angle,
+    };
+    return Arm {
+        start_location,
+        pivot_location,
+        upper_arm_rect: segment.clone(),
+        forearm_rect: segment,
+        shoulder: Joint {
+            speed: 720.0,
+            ..Joint::new(angle, -90.0, 90.0)
+        },
+        elbow: Joint::new(0.0, -100.0, 0.0),
+        upper_arm_length: height * 0.5,
+        forearm_length: height * 0.5,
+        thrown: false,
+        projectile: Projectile::default(),
+        hand_rect: Rectangle {
+            position: start_location,
+            height: width,
+            width,
+            rotation: angle,
+        },
+    }
+    .with_segments();
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::f32::consts::PI;
+
+    use super::*;
+
+    #[test]
+    fn test_straight_arm_swings_as_one() {
+        let arm = Arm {
+            shoulder: Joint {
+                speed: 360.0,
+                ..Joint::new(30.0, -90.0, 90.0)
+            },
+            ..new_arm(vec2(0.0, 0.0))
+        }
+        .with_segments();
+        assert!(
+            (arm.hand_position() - vec2(0.45, 0.45 - 0.9 * 30_f32.to_radians().cos())).length()
+                < 1e-5
+        );
+        let expected = vec2(30_f32.to_radians().cos(), 30_f32.to_radians().sin()) * 2.0 * PI * 0.9;
+        assert!((arm.hand_velocity() - expected).length() < 1e-4);
+        assert_eq!(arm.forearm_rect.rotation, 30.0);
+    }
+
+    #[test]
+    fn test_klapi_leaves_from_hand() {
+        let mut arm = new_arm(vec2(0.0, 0.0)).posed(-90.0).swing(720.0);
+        for _ in 0..80 {
+            let klapi = arm.launch_klapi();
+            assert_eq!(klapi.rect.position, arm.hand_position());
+            assert_eq!(klapi.rect.rotation, arm.forearm_angle());
+            assert_eq!(arm.hand_rect.position, arm.hand_position());
+            // A klapi let go now is where the hand would have carried it a
+            // step later.
+            let next = arm.update(1.0 / 120.0);
+            let flown = klapi.update(1.0 / 120.0);
+            assert!(flown.rect.position.distance(next.hand_position()) < 0.005);
+            // It keeps turning with the hand, too.
+            assert_eq!(klapi.rotational_speed, arm.forearm_speed());
+            assert!((flown.rect.rotation - next.forearm_angle()).abs() < 0.1);
+            arm = next;
+        }
+    }
+
+    /// Swings a wound up arm until its shoulder reaches `angle`.
+    fn swing_to(arm: &Arm, angle: f32) -> Arm {
+        let mut arm = arm.swing(720.0);
+        while arm.shoulder.angle < angle {
+            arm = arm.update(1.0 / 120.0);
+        }
+        return arm;
+    }
+
+    #[test]
+    fn test_elbow_whip() {
+        let arm = new_arm(vec2(0.0, 0.0));
+        let wound_up = Arm {
+            shoulder: Joint::new(-90.0, -90.0, 90.0),
+            elbow: Joint::new(-100.0, -100.0, 0.0),
+            ..arm.clone()
+        };
+        let stiff = Arm {
+            elbow: Joint::locked(0.0),
+            ..wound_up.clone()
+        };
+        let whipping = swing_to(&wound_up, 20.0);
+        let straight = swing_to(&stiff, 20.0);
+        assert!(whipping.elbow.angle < 0.0);
+        assert!(whipping.elbow.turning_speed() > 0.0);
+        assert!(whipping.hand_velocity().length() > straight.hand_velocity().length());
+
+        // Once the elbow is straight it no longer adds to the throw.
+        let late = swing_to(&wound_up, 80.0);
+        assert_eq!(late.elbow.angle, 0.0);
+        assert_eq!(late.elbow.turning_speed(), 0.0);
+        let expected = late.shoulder.speed.to_radians() * late.reach();
+        assert!((late.hand_velocity().length() - expected).abs() < 1e-3);
+    }
+}
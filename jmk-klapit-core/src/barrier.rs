@@ -0,0 +1,235 @@
+use glam::Vec2;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::geometry::{Contact, Rectangle};
+use crate::klapi::Klapi;
+use crate::material::Material;
+
+/// Contacts approaching slower than this do not bounce, so a klapi lying on a
+/// barrier settles instead of jittering on it.
+const MIN_BOUNCE_SPEED: f32 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "BarrierFields")]
+pub struct Barrier {
+    pub material: Material,
+    pub rect: Rectangle,
+}
+
+/// A barrier as written in a level file. Levels saved before barriers had
+/// materials give a bounciness and a friction instead.
+#[derive(Deserialize)]
+#[serde(rename = "Barrier")]
+struct BarrierFields {
+    #[serde(default, deserialize_with = "present")]
+    material: Option<Material>,
+    #[serde(default, deserialize_with = "present")]
+    bounciness: Option<f32>,
+    #[serde(default, deserialize_with = "present")]
+    friction: Option<f32>,
+    rect: Rectangle,
+}
+
+/// Reads a field that is optional in the file but written without `Some`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    return T::deserialize(deserializer).map(Some);
+}
+
+impl TryFrom<BarrierFields> for Barrier {
+    type Error = String;
+
+    fn try_from(fields: BarrierFields) -> Result<Self, Self::Error> {
+        let material = match (fields.material, fields.bounciness, fields.friction) {
+            (Some(material), _, _) => material,
+            (None, Some(bounciness), Some(friction)) => Material {
+                name: String::from("Custom"),
+                restitution: bounciness,
+                static_friction: friction,
+                dynamic_friction: friction,
+                sound: None,
+                particles: None,
+            },
+            _ => return Err(String::from("barrier needs a material")),
+        };
+        return Ok(Barrier {
+            material,
+            rect: fields.rect,
+        });
+    }
+}
+
+impl Barrier {
+    /// Resolves a collision with an impulse at the contact point, using the
+    /// mix of the barrier's and the klapi's materials. Restitution acts along
+    /// the contact normal and friction across it, so off-centre hits and
+    /// sliding make the klapi spin. A contact that static friction can hold
+    /// stops sliding, otherwise dynamic friction slows it.
+    pub fn on_collision(&self, klapi: &Klapi, contact: &Contact) -> Klapi {
+        let mut result = klapi.clone();
+        let point = contact.points.iter().sum::<Vec2>() / contact.points.len() as f32;
+        let normal = contact.normal;
+        let normal_speed = result.velocity_at(point).dot(normal);
+        if normal_speed < 0.0 {
+            let mix = self.material.mix(&klapi.material);
+            let restitution = if normal_speed > -MIN_BOUNCE_SPEED {
+                0.0
+            } else {
+                mix.restitution
+            };
+            let normal_impulse =
+                -(1.0 + restitution) * normal_speed / result.inverse_mass_at(point, normal);
+            result.apply_impulse(normal * normal_impulse, point);
+
+            let velocity = result.velocity_at(point);
+            let tangent = (velocity - normal * velocity.dot(normal)).normalize_or_zero();
+            if tangent != Vec2::ZERO {
+                let stopping_impulse =
+                    -velocity.dot(tangent) / result.inverse_mass_at(point, tangent);
+                let friction_impulse =
+                    if stopping_impulse.abs() <= mix.static_friction * normal_impulse {
+                        stopping_impulse
+                    } else {
+                        stopping_impulse.signum() * mix.dynamic_friction * normal_impulse
+                    };
+                result.apply_impulse(tangent * friction_impulse, point);
+            }
+        }
+        result.rect = self.move_klapi_out_of(klapi, contact);
+        return result;
+    }
+
+    /// Pushes the klapi out of the barrier along the contact normal, which
+    /// points from the barrier towards the klapi.
+    fn move_klapi_out_of(&self, klapi: &Klapi, contact: &Contact) -> Rectangle {
+        let mut rect = klapi.rect.clone();
+        rect.position += contact.normal * contact.depth;
+        return rect;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kiuas {
+    pub barriers: Vec<Barrier>,
+    pub goal: Rectangle,
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+    use crate::klapi::tests::test_klapi;
+
+    pub(crate) fn test_barrier(position: Vec2, width: f32, height: f32) -> Barrier {
+        Barrier {
+            material: Material {
+                restitution: 0.5,
+                static_friction: 0.0,
+                dynamic_friction: 0.0,
+                ..Material::stone()
+            },
+            rect: Rectangle {
+                position,
+                width,
+                height,
+                rotation: 0.0,
+            },
+        }
+    }
+
+    /// A klapi made of the same material as `barrier`, so their contact
+    /// behaves exactly as the barrier's material says.
+    fn klapi_like(barrier: &Barrier, position: Vec2, speed: Vec2) -> Klapi {
+        let mut klapi = test_klapi(position, speed);
+        klapi.material = barrier.material.clone();
+        return klapi;
+    }
+
+    #[test]
+    fn test_collision_keeps_tangential_speed() {
+        let floor = test_barrier(vec2(0.0, -1.0), 20.0, 0.2);
+        let klapi = klapi_like(&floor, vec2(1.0, -0.85), vec2(3.0, -4.0));
+        let contact = floor.rect.contact(&klapi.rect).unwrap();
+        let result = floor.on_collision(&klapi, &contact);
+        assert_eq!(result.speed, vec2(3.0, 2.0));
+        assert_eq!(floor.rect.collide(&result.rect), false);
+    }
+
+    #[test]
+    fn test_collision_bounces_back_from_side() {
+        let wall = test_barrier(vec2(0.0, 0.0), 1.0, 0.8);
+        let klapi = klapi_like(&wall, vec2(-0.65, 0.0), vec2(4.0, 1.0));
+        let contact = wall.rect.contact(&klapi.rect).unwrap();
+        let result = wall.on_collision(&klapi, &contact);
+        assert_eq!(result.speed, vec2(-2.0, 1.0));
+        assert_eq!(wall.rect.collide(&result.rect), false);
+    }
+
+    #[test]
+    fn test_off_centre_collision_spins_klapi() {
+        let floor = test_barrier(vec2(0.0, -1.0), 20.0, 0.2);
+        let mut klapi = klapi_like(&floor, vec2(0.0, -0.74), vec2(0.0, -3.0));
+        klapi.rect.rotation = 30.0;
+        let contact = floor.rect.contact(&klapi.rect).unwrap();
+        assert_eq!(contact.points.len(), 1);
+        let result = floor.on_collision(&klapi, &contact);
+        assert!(result.speed.y > klapi.speed.y);
+        assert!(result.rotational_speed < 0.0);
+        let mut unmoved = result.clone();
+        unmoved.rect = klapi.rect.clone();
+        assert!(unmoved.velocity_at(contact.points[0]).y > 0.0);
+    }
+
+    #[test]
+    fn test_friction_turns_sliding_into_spin() {
+        let mut floor = test_barrier(vec2(0.0, -1.0), 20.0, 0.2);
+        floor.material.static_friction = 0.5;
+        floor.material.dynamic_friction = 0.5;
+        let klapi = klapi_like(&floor, vec2(0.0, -0.85), vec2(4.0, -2.0));
+        let contact = floor.rect.contact(&klapi.rect).unwrap();
+        let result = floor.on_collision(&klapi, &contact);
+        assert!(result.speed.x < 4.0);
+        assert!(result.rotational_speed < 0.0);
+    }
+
+    #[test]
+    fn test_static_friction_holds_slow_slides() {
+        let mut floor = test_barrier(vec2(0.0, -1.0), 20.0, 0.2);
+        floor.material.static_friction = 0.5;
+        floor.material.dynamic_friction = 0.02;
+        let slide = |speed: f32| {
+            let klapi = klapi_like(&floor, vec2(0.0, -0.85), vec2(speed, -2.0));
+            let contact = floor.rect.contact(&klapi.rect).unwrap();
+            let mut result = floor.on_collision(&klapi, &contact);
+            result.rect = klapi.rect.clone();
+            let point = contact.points.iter().sum::<Vec2>() / contact.points.len() as f32;
+            result.velocity_at(point).x
+        };
+        assert!(slide(0.3).abs() < 1e-4);
+        assert!(slide(4.0) > 3.5);
+    }
+
+    #[test]
+    fn test_collision_mixes_materials() {
+        let floor = Barrier {
+            material: Material::stone(),
+            ..test_barrier(vec2(0.0, -1.0), 20.0, 0.2)
+        };
+        let mut klapi = test_klapi(vec2(0.0, -0.85), vec2(4.0, -2.0));
+        klapi.material = Material::wood();
+        let contact = floor.rect.contact(&klapi.rect).unwrap();
+        let result = floor.on_collision(&klapi, &contact);
+        // Stone and wood bounce with sqrt(0.8 * 0.6) and slide with a
+        // dynamic friction of sqrt(0.5 * 0.45).
+        let restitution = (0.8_f32 * 0.6).sqrt();
+        let normal_impulse = (1.0 + restitution) * 2.0 * klapi.mass;
+        let friction_impulse = (0.5_f32 * 0.45).sqrt() * normal_impulse;
+        assert!((result.speed.y - 2.0 * restitution).abs() < 1e-4);
+        assert!((result.speed.x - (4.0 - friction_impulse / klapi.mass)).abs() < 1e-4);
+    }
+}
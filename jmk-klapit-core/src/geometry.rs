@@ -0,0 +1,521 @@
+use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+pub fn round_to_precision(number: f32, precision: i32) -> f32 {
+    let val: f32 = number * 10.0_f32.powi(precision);
+    let rounded_val = val.round();
+    return rounded_val / 10.0_f32.powi(precision);
+}
+
+pub struct Line {
+    pub start: Vec2,
+    pub end: Vec2,
+}
+
+impl Line {
+    pub fn collide(&self, other: &Line) -> bool {
+        let denominator = ((self.end.x - self.start.x) * (other.end.y - other.start.y))
+            - ((self.end.y - self.start.y) * (other.end.x - other.start.x));
+        let numerator1 = ((self.start.y - other.start.y) * (other.end.x - other.start.x))
+            - ((self.start.x - other.start.x) * (other.end.y - other.start.y));
+        let numerator2 = ((self.start.y - other.start.y) * (self.end.x - self.start.x))
+            - ((self.start.x - other.start.x) * (self.end.y - self.start.y));
+        if denominator == 0.0 {
+            return numerator1 == 0.0 && numerator2 == 0.0;
+        }
+        let r = numerator1 / denominator;
+        let s = numerator2 / denominator;
+        return (0.0..=1.0).contains(&r) && (0.0..=1.0).contains(&s);
+    }
+}
+
+/// Result of a collision between two convex polygons.
+#[derive(Debug, Clone)]
+pub struct Contact {
+    /// Unit normal pointing from the first polygon towards the second.
+    pub normal: Vec2,
+    /// How far the polygons overlap along `normal`.
+    pub depth: f32,
+    /// Points where the polygons touch, one or two of them.
+    pub points: Vec<Vec2>,
+}
+
+pub struct Polygon {
+    pub vertices: Vec<Line>,
+}
+
+impl Polygon {
+    pub fn collide_point(&self, point: Vec2) -> bool {
+        let mut collision = false;
+        for vertex in &self.vertices {
+            let val = ((vertex.start.y > point.y && vertex.end.y < point.y)
+                || (vertex.start.y < point.y && vertex.end.y > point.y))
+                && (point.x
+                    < (vertex.end.x - vertex.start.x) * (point.y - vertex.start.y)
+                        / (vertex.end.y - vertex.start.y)
+                        + vertex.start.x);
+            if val {
+                collision = !collision
+            }
+        }
+        collision
+    }
+    pub fn collide_line(&self, line: &Line) -> bool {
+        for vertice in &self.vertices {
+            if line.collide(vertice) {
+                return true;
+            }
+        }
+        return false;
+    }
+
+    pub fn collide(&self, other: &Polygon) -> bool {
+        return self.contact(other).is_some();
+    }
+
+    /// Separating axis test between two convex polygons. Returns `None` when
+    /// the polygons are apart or only touch. The contact normal points from
+    /// `self` towards `other`.
+    pub fn contact(&self, other: &Polygon) -> Option<Contact> {
+        let (self_separation, self_edge) = self.max_separation(other)?;
+        let (other_separation, other_edge) = other.max_separation(self)?;
+        let flip = other_separation > self_separation + 1e-4;
+        let (reference, incident, edge, separation) = if flip {
+            (other, self, other_edge, other_separation)
+        } else {
+            (self, other, self_edge, self_separation)
+        };
+        let reference_edge = &reference.vertices[edge];
+        let reference_normal = reference.outward_normal(reference_edge);
+        let points = clip_incident_edge(incident, reference_edge, reference_normal);
+        let normal = if flip {
+            -reference_normal
+        } else {
+            reference_normal
+        };
+        return Some(Contact {
+            normal,
+            depth: -separation,
+            points,
+        });
+    }
+
+    fn is_clockwise(&self) -> bool {
+        let mut area = 0.0;
+        for vertice in &self.vertices {
+            area += vertice.start.x * vertice.end.y - vertice.end.x * vertice.start.y;
+        }
+        area < 0.0
+    }
+
+    fn outward_normal(&self, edge: &Line) -> Vec2 {
+        let direction = edge.end - edge.start;
+        let normal = if self.is_clockwise() {
+            vec2(-direction.y, direction.x)
+        } else {
+            vec2(direction.y, -direction.x)
+        };
+        normal.normalize_or_zero()
+    }
+
+    /// Largest signed distance of `other` from the faces of `self` and the
+    /// index of that face. `None` when a face separates the polygons.
+    fn max_separation(&self, other: &Polygon) -> Option<(f32, usize)> {
+        let mut best: Option<(f32, usize)> = None;
+        for (index, edge) in self.vertices.iter().enumerate() {
+            let normal = self.outward_normal(edge);
+            let separation = other
+                .vertices
+                .iter()
+                .map(|vertice| normal.dot(vertice.start - edge.start))
+                .fold(f32::INFINITY, f32::min);
+            if separation >= 0.0 {
+                return None;
+            }
+            match best {
+                Some((best_separation, _)) if best_separation >= separation => {}
+                _ => best = Some((separation, index)),
+            }
+        }
+        best
+    }
+}
+
+/// Clips the edge of `incident` facing the reference edge against the
+/// sides of the reference edge and keeps the points below its face.
+fn clip_incident_edge(incident: &Polygon, edge: &Line, normal: Vec2) -> Vec<Vec2> {
+    let incident_edge = incident
+        .vertices
+        .iter()
+        .min_by(|a, b| {
+            let a_dot = incident.outward_normal(a).dot(normal);
+            let b_dot = incident.outward_normal(b).dot(normal);
+            a_dot.total_cmp(&b_dot)
+        })
+        .expect("polygon has no edges");
+    let tangent = (edge.end - edge.start).normalize_or_zero();
+    let mut points = vec![incident_edge.start, incident_edge.end];
+    points = clip_segment(&points, -tangent, -tangent.dot(edge.start));
+    points = clip_segment(&points, tangent, tangent.dot(edge.end));
+    let mut contact_points: Vec<Vec2> = points
+        .into_iter()
+        .filter(|point| normal.dot(*point - edge.start) <= 0.0)
+        .collect();
+    if contact_points.is_empty() {
+        let deepest = incident
+            .vertices
+            .iter()
+            .map(|vertice| vertice.start)
+            .min_by(|a, b| normal.dot(*a).total_cmp(&normal.dot(*b)))
+            .expect("polygon has no edges");
+        contact_points.push(deepest);
+    }
+    contact_points
+}
+
+/// Keeps the part of the segment `points` on the side of the line
+/// `direction . p = offset` where `direction . p <= offset`.
+fn clip_segment(points: &[Vec2], direction: Vec2, offset: f32) -> Vec<Vec2> {
+    if points.len() < 2 {
+        return points.to_vec();
+    }
+    let distance_a = direction.dot(points[0]) - offset;
+    let distance_b = direction.dot(points[1]) - offset;
+    let mut clipped = Vec::with_capacity(2);
+    if distance_a <= 0.0 {
+        clipped.push(points[0]);
+    }
+    if distance_b <= 0.0 {
+        clipped.push(points[1]);
+    }
+    if distance_a * distance_b < 0.0 {
+        let t = distance_a / (distance_a - distance_b);
+        clipped.push(points[0] + (points[1] - points[0]) * t);
+    }
+    clipped
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Rectangle {
+    pub position: Vec2,
+    pub width: f32,
+    pub height: f32,
+    pub rotation: f32,
+}
+
+impl Rectangle {
+    pub fn to_poly(&self) -> Polygon {
+        let tl = self.top_left();
+        let tr = self.top_right();
+        let br = self.bottom_right();
+        let bl = self.bottom_left();
+        return Polygon {
+            vertices: vec![
+                Line { start: tl, end: tr },
+                Line { start: tr, end: br },
+                Line { start: br, end: bl },
+                Line { start: bl, end: tl },
+            ],
+        };
+    }
+
+    pub fn collide(&self, rect: &Rectangle) -> bool {
+        return self.to_poly().collide(&rect.to_poly());
+    }
+
+    pub fn contact(&self, rect: &Rectangle) -> Option<Contact> {
+        return self.to_poly().contact(&rect.to_poly());
+    }
+
+    pub fn top_left(&self) -> Vec2 {
+        let theta = self.rotation.to_radians();
+        let x = self.position.x
+            - ((self.width * 0.5) * theta.cos())
+            - ((self.height * 0.5) * theta.sin());
+        let y = self.position.y - ((self.width * 0.5) * theta.sin())
+            + ((self.height * 0.5) * theta.cos());
+        return vec2(x, y);
+    }
+
+    pub fn top_right(&self) -> Vec2 {
+        let theta = self.rotation.to_radians();
+        let x = self.position.x + ((self.width * 0.5) * theta.cos())
+            - ((self.height * 0.5) * theta.sin());
+        let y = self.position.y
+            + ((self.width * 0.5) * theta.sin())
+            + ((self.height * 0.5) * theta.cos());
+        return vec2(x, y);
+    }
+
+    pub fn bottom_right(&self) -> Vec2 {
+        let theta = self.rotation.to_radians();
+        let x = self.position.x
+            + ((self.width * 0.5) * theta.cos())
+            + ((self.height * 0.5) * theta.sin());
+        let y = self.position.y + ((self.width * 0.5) * theta.sin())
+            - ((self.height * 0.5) * theta.cos());
+        return vec2(x, y);
+    }
+
+    pub fn bottom_left(&self) -> Vec2 {
+        let theta = self.rotation.to_radians();
+        let x = self.position.x - ((self.width * 0.5) * theta.cos())
+            + ((self.height * 0.5) * theta.sin());
+        let y = self.position.y
+            - ((self.width * 0.5) * theta.sin())
+            - ((self.height * 0.5) * theta.cos());
+        return vec2(x, y);
+    }
+
+    pub fn pivot(&self, pivot_point: Vec2, angle: f32) -> Rectangle {
+        let theta = angle.to_radians();
+        let centered_x = self.position.x - pivot_point.x;
+        let centered_y = self.position.y - pivot_point.y;
+        let d_x = theta.cos() * centered_x - theta.sin() * centered_y;
+        let d_y = theta.sin() * centered_x + theta.cos() * centered_y;
+        let pos_x = pivot_point.x + d_x;
+        let pos_y = pivot_point.y + d_y;
+        let position = vec2(round_to_precision(pos_x, 6), round_to_precision(pos_y, 6));
+        return Rectangle {
+            position,
+            width: self.width,
+            height: self.height,
+            rotation: angle,
+        };
+    }
+
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.to_poly().collide_point(point)
+    }
+
+    pub fn corners(&self) -> [Vec2; 4] {
+        return [
+            self.top_left(),
+            self.top_right(),
+            self.bottom_right(),
+            self.bottom_left(),
+        ];
+    }
+
+    /// Linear interpolation between two poses of the same rectangle.
+    pub fn lerp(&self, other: &Rectangle, alpha: f32) -> Rectangle {
+        return Rectangle {
+            position: self.position.lerp(other.position, alpha),
+            width: other.width,
+            height: other.height,
+            rotation: self.rotation + (other.rotation - self.rotation) * alpha,
+        };
+    }
+
+    /// The same rectangle grown by `margin` on every side.
+    pub fn expanded(&self, margin: f32) -> Rectangle {
+        return Rectangle {
+            width: self.width + margin * 2.0,
+            height: self.height + margin * 2.0,
+            ..self.clone()
+        };
+    }
+}
+
+impl Clone for Rectangle {
+    fn clone(&self) -> Self {
+        Rectangle {
+            position: self.position,
+            width: self.width,
+            height: self.height,
+            rotation: self.rotation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_collide_right() {
+        let rect1 = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        let rect2 = Rectangle {
+            position: vec2(5.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+
+        let result = rect1.collide(&rect2);
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn test_collide_left() {
+        let rect1 = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        let rect2 = Rectangle {
+            position: vec2(-5.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+
+        let result = rect1.collide(&rect2);
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn test_collide_top() {
+        let rect1 = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        let rect2 = Rectangle {
+            position: vec2(0.0, 5.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+
+        let result = rect1.collide(&rect2);
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn test_collide_bottom() {
+        let rect1 = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        let rect2 = Rectangle {
+            position: vec2(-5.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+
+        let result = rect1.collide(&rect2);
+        assert_eq!(result, true);
+    }
+
+    #[test]
+    fn test_collide_contained() {
+        let outer = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        let inner = Rectangle {
+            position: vec2(1.0, 1.0),
+            width: 2.0,
+            height: 2.0,
+            rotation: 30.0,
+        };
+        assert_eq!(outer.collide(&inner), true);
+        assert_eq!(inner.collide(&outer), true);
+    }
+
+    #[test]
+    fn test_contact_manifold() {
+        let floor = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 2.0,
+            rotation: 0.0,
+        };
+        let block = Rectangle {
+            position: vec2(1.0, 1.9),
+            width: 2.0,
+            height: 2.0,
+            rotation: 0.0,
+        };
+        let contact = floor.contact(&block).unwrap();
+        assert!((contact.normal - vec2(0.0, 1.0)).length() < 1e-6);
+        assert!((contact.depth - 0.1).abs() < 1e-5);
+        assert_eq!(contact.points.len(), 2);
+        for point in &contact.points {
+            assert!((point.y - 0.9).abs() < 1e-5);
+        }
+        let flipped = block.contact(&floor).unwrap();
+        assert!((flipped.normal - vec2(0.0, -1.0)).length() < 1e-6);
+        assert!((flipped.depth - 0.1).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_contact_corner() {
+        let floor = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 10.0,
+            height: 2.0,
+            rotation: 0.0,
+        };
+        let tilted = Rectangle {
+            position: vec2(0.0, 1.6),
+            width: 1.0,
+            height: 1.0,
+            rotation: 45.0,
+        };
+        let contact = floor.contact(&tilted).unwrap();
+        assert!((contact.normal - vec2(0.0, 1.0)).length() < 1e-6);
+        assert_eq!(contact.points.len(), 1);
+        assert!(contact.points[0].x.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_pivot() {
+        let rect = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 5.0,
+            height: 10.0,
+            rotation: 0.0,
+        };
+        let result = rect.pivot(vec2(0.0, 0.0), 90.0);
+        assert_eq!(result.position, rect.position);
+        let result3 = rect.pivot(vec2(0.0, 5.0), 45.0);
+        assert_eq!(result3.position, vec2(3.535534, 1.464466));
+    }
+
+    #[test]
+    fn test_pivot_tiny_scale() {
+        let rect = Rectangle {
+            position: vec2(0.0, 0.0),
+            width: 0.18,
+            height: 0.80,
+            rotation: 0.0,
+        };
+        let result = rect.pivot(rect.position, 90.0);
+        assert_eq!(result.position, rect.position);
+        let result3 = rect.pivot(vec2(0.0, 0.40), 45.0);
+        assert_eq!(result3.position, vec2(0.282843, 0.117157));
+    }
+
+    #[test]
+    fn test_bug_in_goal_collision() {
+        let goal = Rectangle {
+            position: vec2(2.0, -1.3),
+            width: 0.3,
+            height: 0.2,
+            rotation: 0.0,
+        };
+        let klapi = Rectangle {
+            position: vec2(5.933142, -1.0068376),
+            width: 0.45,
+            height: 0.15,
+            rotation: 38.309246,
+        };
+        assert_eq!(goal.collide(&klapi), false);
+        assert_eq!(klapi.collide(&goal), false);
+    }
+}
@@ -0,0 +1,212 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Scores kept for each level and mode.
+pub const HIGH_SCORES_KEPT: usize = 10;
+
+/// One finished round in the high-score table.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HighScore {
+    pub name: String,
+    pub level: String,
+    pub mode: String,
+    pub score: u32,
+    pub throws: u32,
+    /// Day the round was played, as `YYYY-MM-DD`.
+    pub date: String,
+}
+
+/// Best rounds played on this machine. Stored as a RON file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+    /// Names of the players of the last game recorded, so the next session
+    /// starts with them.
+    #[serde(default)]
+    pub last_players: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum HighScoreError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for HighScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HighScoreError::Io(error) => {
+                write!(f, "could not read or write high scores: {}", error)
+            }
+            HighScoreError::Parse(error) => write!(f, "invalid high score file: {}", error),
+            HighScoreError::Serialize(error) => {
+                write!(f, "could not serialize high scores: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HighScoreError {}
+
+impl From<std::io::Error> for HighScoreError {
+    fn from(error: std::io::Error) -> Self {
+        HighScoreError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for HighScoreError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        HighScoreError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for HighScoreError {
+    fn from(error: ron::Error) -> Self {
+        HighScoreError::Serialize(error)
+    }
+}
+
+impl HighScores {
+    /// Reads the table in `path`. Modes written before the control scheme
+    /// was part of them, such as `"Full preview"`, were all played with
+    /// timed presses and are moved to that scheme.
+    pub fn load(path: impl AsRef<Path>) -> Result<HighScores, HighScoreError> {
+        let source = fs::read_to_string(path)?;
+        let mut scores: HighScores = ron::from_str(&source)?;
+        for entry in &mut scores.entries {
+            if entry.mode.ends_with(" preview") {
+                entry.mode.push_str(", Timing");
+            }
+        }
+        return Ok(scores);
+    }
+
+    /// Writes the table to `path`, creating its directory if needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), HighScoreError> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let config = ron::ser::PrettyConfig::new().struct_names(true);
+        fs::write(path, ron::ser::to_string_pretty(self, config)?)?;
+        return Ok(());
+    }
+
+    /// Best scores of a level and mode, highest first. Equal scores keep the
+    /// order they were set in.
+    pub fn top(&self, level: &str, mode: &str) -> Vec<&HighScore> {
+        let mut scores: Vec<&HighScore> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.level == level && entry.mode == mode)
+            .collect();
+        scores.sort_by_key(|score| Reverse(score.score));
+        scores.truncate(HIGH_SCORES_KEPT);
+        return scores;
+    }
+
+    /// Adds a round and drops the scores of its level and mode that no longer
+    /// make the table. Returns the place the round got, starting from 0, or
+    /// `None` when it did not make it.
+    pub fn add(&mut self, score: HighScore) -> Option<usize> {
+        let (level, mode) = (score.level.clone(), score.mode.clone());
+        self.entries.push(score);
+        let kept: Vec<HighScore> = self.top(&level, &mode).into_iter().cloned().collect();
+        let place = kept
+            .iter()
+            .rposition(|entry| Some(entry) == self.entries.last());
+        self.entries
+            .retain(|entry| entry.level != level || entry.mode != mode);
+        self.entries.extend(kept);
+        return place;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn high_score(name: &str, mode: &str, score: u32) -> HighScore {
+        HighScore {
+            name: String::from(name),
+            level: String::from("Sauna"),
+            mode: String::from(mode),
+            score,
+            throws: 10,
+            date: String::from("2024-02-29"),
+        }
+    }
+
+    #[test]
+    fn test_high_scores_per_mode() {
+        let mut scores = HighScores::default();
+        assert_eq!(scores.add(high_score("Aino", "Short", 30)), Some(0));
+        assert_eq!(scores.add(high_score("Eero", "Short", 50)), Some(0));
+        assert_eq!(scores.add(high_score("Ilmari", "Off", 10)), Some(0));
+        let top = scores.top("Sauna", "Short");
+        assert_eq!(top[0].name, "Eero");
+        assert_eq!(top[1].name, "Aino");
+        assert_eq!(scores.top("Sauna", "Off").len(), 1);
+        assert!(scores.top("Lakeside", "Short").is_empty());
+    }
+
+    #[test]
+    fn test_high_scores_are_capped() {
+        let mut scores = HighScores::default();
+        for score in 1..=HIGH_SCORES_KEPT as u32 {
+            scores.add(high_score("Aino", "Short", score * 10));
+        }
+        assert_eq!(scores.add(high_score("Eero", "Short", 0)), None);
+        assert_eq!(scores.add(high_score("Eero", "Short", 45)), Some(6));
+        let top = scores.top("Sauna", "Short");
+        assert_eq!(top.len(), HIGH_SCORES_KEPT);
+        assert_eq!(top.last().unwrap().score, 20);
+    }
+
+    #[test]
+    fn test_high_scores_round_trip() {
+        let path = std::env::temp_dir().join("jmk-klapit-test/highscores.ron");
+        let mut scores = HighScores::default();
+        scores.add(high_score("Aino", "Short", 30));
+        scores.save(&path).unwrap();
+        let loaded = HighScores::load(&path).unwrap();
+        assert_eq!(loaded.entries, scores.entries);
+        assert!(loaded.last_players.is_empty());
+        scores.last_players = vec![String::from("Eero"), String::from("Aino")];
+        scores.add(high_score("Eero", "Short", 50));
+        scores.save(&path).unwrap();
+        let loaded = HighScores::load(&path).unwrap();
+        assert_eq!(loaded.last_players, ["Eero", "Aino"]);
+        assert_eq!(loaded.entries.last().unwrap().name, "Aino");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_load_modes_without_controls() {
+        let path = std::env::temp_dir().join("jmk-klapit-test/old-highscores.ron");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let source = r#"HighScores(
+            entries: [
+                HighScore(
+                    name: "Aino",
+                    level: "Sauna",
+                    mode: "Full preview",
+                    score: 30,
+                    throws: 10,
+                    date: "2024-02-29",
+                ),
+            ],
+        )"#;
+        fs::write(&path, source).unwrap();
+        let loaded = HighScores::load(&path).unwrap();
+        assert_eq!(loaded.top("Sauna", "Full preview, Timing")[0].name, "Aino");
+        assert!(loaded.top("Sauna", "Full preview").is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,217 @@
+use crate::geometry::Rectangle;
+use crate::round::{Round, ThrowRecord};
+use crate::simulation::{Flight, ThrowResult};
+
+/// Most players sharing one arm.
+pub const MAX_PLAYERS: usize = 8;
+
+/// Someone taking turns at the arm, with their own round and the extra
+/// throws they made to break a tie for first place.
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub name: String,
+    pub round: Round,
+    pub tie_break: Round,
+}
+
+impl Player {
+    fn new(name: String, round_length: u32) -> Player {
+        Player {
+            name,
+            round: Round::new(round_length),
+            tie_break: Round::new(0),
+        }
+    }
+
+    /// The round being played: the regular one, or the tie-break once the
+    /// regular rounds are over.
+    fn current_round(&self, tie_break: bool) -> &Round {
+        if tie_break {
+            &self.tie_break
+        } else {
+            &self.round
+        }
+    }
+}
+
+/// Players taking turns throwing with the same arm. Everyone throws once per
+/// turn until their rounds are over. Players sharing the best score then
+/// throw one tie-break throw each until one of them scores more.
+#[derive(Debug, Clone)]
+pub struct HotSeat {
+    pub players: Vec<Player>,
+    /// Index of the player whose turn it is.
+    pub turn: usize,
+    /// Players still in the tie-break. Empty outside of one.
+    pub tie_break: Vec<usize>,
+}
+
+impl HotSeat {
+    /// Starts a game for `names`, of which there are 1 to `MAX_PLAYERS`.
+    pub fn new(names: Vec<String>, round_length: u32) -> HotSeat {
+        assert!((1..=MAX_PLAYERS).contains(&names.len()));
+        HotSeat {
+            players: names
+                .into_iter()
+                .map(|name| Player::new(name, round_length))
+                .collect(),
+            turn: 0,
+            tie_break: Vec::new(),
+        }
+    }
+
+    pub fn current(&self) -> &Player {
+        return &self.players[self.turn];
+    }
+
+    /// The round the current player is throwing in.
+    pub fn current_round(&self) -> &Round {
+        return self.current().current_round(self.in_tie_break());
+    }
+
+    pub fn in_tie_break(&self) -> bool {
+        return !self.tie_break.is_empty();
+    }
+
+    /// Scores a throw of the current player.
+    pub fn record(
+        &mut self,
+        result: ThrowResult,
+        flight: &Flight,
+        goal: &Rectangle,
+    ) -> &ThrowRecord {
+        let player = &mut self.players[self.turn];
+        if self.tie_break.is_empty() {
+            return player.round.record(result, flight, goal);
+        }
+        return player.tie_break.record(result, flight, goal);
+    }
+
+    /// Hands the arm to the next player with throws left. When everyone is
+    /// done and the best score is shared, the tied players get one more
+    /// throw each.
+    pub fn pass_turn(&mut self) {
+        let tie_break = self.in_tie_break();
+        let count = self.players.len();
+        let next = (1..=count)
+            .map(|offset| (self.turn + offset) % count)
+            .find(|&index| !self.players[index].current_round(tie_break).is_over());
+        if let Some(index) = next {
+            self.turn = index;
+            return;
+        }
+        let contenders = if tie_break {
+            self.tie_break.clone()
+        } else {
+            (0..count).collect()
+        };
+        let leaders = self.leaders(&contenders, tie_break);
+        if leaders.len() > 1 {
+            for &index in &leaders {
+                self.players[index].tie_break.length += 1;
+            }
+            self.turn = leaders[0];
+            self.tie_break = leaders;
+        } else {
+            self.tie_break.clear();
+        }
+    }
+
+    /// The contenders sharing the best score.
+    fn leaders(&self, contenders: &[usize], tie_break: bool) -> Vec<usize> {
+        let score = |index: usize| self.players[index].current_round(tie_break).score();
+        let best = contenders.iter().map(|&index| score(index)).max();
+        return contenders
+            .iter()
+            .copied()
+            .filter(|&index| Some(score(index)) == best)
+            .collect();
+    }
+
+    pub fn is_over(&self) -> bool {
+        return !self.in_tie_break() && self.players.iter().all(|player| player.round.is_over());
+    }
+
+    /// Players from first to last. Tie-break scores only order players with
+    /// the same round score.
+    pub fn standings(&self) -> Vec<&Player> {
+        let mut standings: Vec<&Player> = self.players.iter().collect();
+        standings.sort_by_key(|player| {
+            std::cmp::Reverse((player.round.score(), player.tie_break.score()))
+        });
+        return standings;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+    use crate::level::tests::sauna;
+    use crate::scoring::tests::test_flight;
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    /// Records a throw that either goes straight into the goal or lands far
+    /// away from it.
+    fn throw(hotseat: &mut HotSeat, result: ThrowResult) {
+        let goal = sauna().kiuas.goal;
+        let flight = test_flight(goal.position + vec2(0.0, 0.2));
+        hotseat.record(result, &flight, &goal);
+    }
+
+    #[test]
+    fn test_turns_alternate() {
+        let mut hotseat = HotSeat::new(names(&["Aino", "Eero", "Ilmari"]), 2);
+        let mut order = Vec::new();
+        while !hotseat.is_over() {
+            order.push(hotseat.turn);
+            let result = if hotseat.turn == 1 {
+                ThrowResult::Goal
+            } else {
+                ThrowResult::Timeout
+            };
+            throw(&mut hotseat, result);
+            hotseat.pass_turn();
+        }
+        assert_eq!(order, vec![0, 1, 2, 0, 1, 2]);
+        assert_eq!(hotseat.standings()[0].name, "Eero");
+        assert_eq!(hotseat.in_tie_break(), false);
+    }
+
+    #[test]
+    fn test_tie_break() {
+        let mut hotseat = HotSeat::new(names(&["Aino", "Eero", "Ilmari"]), 1);
+        throw(&mut hotseat, ThrowResult::Goal);
+        hotseat.pass_turn();
+        throw(&mut hotseat, ThrowResult::CameToRest);
+        hotseat.pass_turn();
+        throw(&mut hotseat, ThrowResult::Goal);
+        hotseat.pass_turn();
+        assert_eq!(hotseat.is_over(), false);
+        assert_eq!(hotseat.tie_break, vec![0, 2]);
+        assert_eq!(hotseat.turn, 0);
+
+        // Both score again, so the tie-break goes on.
+        throw(&mut hotseat, ThrowResult::Goal);
+        hotseat.pass_turn();
+        assert_eq!(hotseat.turn, 2);
+        throw(&mut hotseat, ThrowResult::Goal);
+        hotseat.pass_turn();
+        assert_eq!(hotseat.tie_break, vec![0, 2]);
+
+        throw(&mut hotseat, ThrowResult::OutOfBounds);
+        hotseat.pass_turn();
+        throw(&mut hotseat, ThrowResult::Goal);
+        hotseat.pass_turn();
+        assert_eq!(hotseat.is_over(), true);
+        let standings = hotseat.standings();
+        assert_eq!(standings[0].name, "Ilmari");
+        assert_eq!(standings[1].name, "Aino");
+        assert_eq!(standings[2].name, "Eero");
+    }
+}
@@ -0,0 +1,610 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Analog values below this are treated as the control at rest, so a worn
+/// stick does not drift.
+pub const DEADZONE: f32 = 0.2;
+/// An action bound to an analog control is pressed once its value reaches
+/// this.
+pub const PRESS_THRESHOLD: f32 = 0.5;
+/// A pressed analog action is released once its value drops below this.
+/// Lower than the press threshold, so a control held near it does not
+/// chatter.
+pub const RELEASE_THRESHOLD: f32 = 0.3;
+
+/// What the player can ask the game to do, independent of the key or
+/// button used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Action {
+    /// Charges, swings and lets go of the klapi, and moves on between
+    /// throws.
+    Throw,
+    /// Gives up on a throw in flight.
+    Reset,
+    AimUp,
+    AimDown,
+    /// Held while letting go to spin the klapi forward.
+    Topspin,
+    /// Held while letting go to spin the klapi backward.
+    Backspin,
+    TogglePreview,
+    SwitchControls,
+    /// Stops the game and shows the pause menu.
+    Pause,
+    ToggleDebug,
+    ToggleEditor,
+}
+
+impl Action {
+    pub const ALL: [Action; 11] = [
+        Action::Throw,
+        Action::Reset,
+        Action::AimUp,
+        Action::AimDown,
+        Action::Topspin,
+        Action::Backspin,
+        Action::TogglePreview,
+        Action::SwitchControls,
+        Action::Pause,
+        Action::ToggleDebug,
+        Action::ToggleEditor,
+    ];
+}
+
+/// Gamepad buttons, named by their position on the pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    Select,
+    Start,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+/// Analog gamepad controls. Sticks range from -1.0 to 1.0, up and right
+/// being positive, and triggers from 0.0 to 1.0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+/// Which way an axis has to be pushed to act.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AxisDirection {
+    Positive,
+    Negative,
+}
+
+/// Something physical an action can be bound to.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Control {
+    /// A keyboard key, by the name the game's input backend knows it by.
+    Key(String),
+    Button(PadButton),
+    Axis(PadAxis, AxisDirection),
+}
+
+impl Control {
+    pub fn is_analog(&self) -> bool {
+        return matches!(self, Control::Axis(_, _));
+    }
+}
+
+impl fmt::Display for Control {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Control::Key(name) => write!(f, "{}", name),
+            Control::Button(button) => write!(f, "Pad {:?}", button),
+            Control::Axis(axis, AxisDirection::Positive) => write!(f, "Pad {:?}+", axis),
+            Control::Axis(axis, AxisDirection::Negative) => write!(f, "Pad {:?}-", axis),
+        }
+    }
+}
+
+/// A change in a control, as reported by the keyboard or a gamepad.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    Pressed(Control),
+    Released(Control),
+    Axis(PadAxis, f32),
+}
+
+/// Which controls trigger which actions. An action can have any number of
+/// controls. Stored as a RON file, so players can rebind them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputMap {
+    pub bindings: BTreeMap<Action, Vec<Control>>,
+}
+
+#[derive(Debug)]
+pub enum InputMapError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for InputMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputMapError::Io(error) => write!(f, "could not read or write controls: {}", error),
+            InputMapError::Parse(error) => write!(f, "invalid controls file: {}", error),
+            InputMapError::Serialize(error) => {
+                write!(f, "could not serialize controls: {}", error)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputMapError {}
+
+impl From<std::io::Error> for InputMapError {
+    fn from(error: std::io::Error) -> Self {
+        InputMapError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for InputMapError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        InputMapError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for InputMapError {
+    fn from(error: ron::Error) -> Self {
+        InputMapError::Serialize(error)
+    }
+}
+
+fn key(name: &str) -> Control {
+    Control::Key(name.to_string())
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        let button = Control::Button;
+        let positive = |axis| Control::Axis(axis, AxisDirection::Positive);
+        let negative = |axis| Control::Axis(axis, AxisDirection::Negative);
+        let bindings = [
+            (
+                Action::Throw,
+                vec![
+                    key("Space"),
+                    button(PadButton::South),
+                    positive(PadAxis::RightTrigger),
+                ],
+            ),
+            (Action::Reset, vec![key("R"), button(PadButton::East)]),
+            (
+                Action::AimUp,
+                vec![
+                    key("Up"),
+                    button(PadButton::DPadUp),
+                    positive(PadAxis::LeftStickY),
+                ],
+            ),
+            (
+                Action::AimDown,
+                vec![
+                    key("Down"),
+                    button(PadButton::DPadDown),
+                    negative(PadAxis::LeftStickY),
+                ],
+            ),
+            (
+                Action::Topspin,
+                vec![
+                    key("Right"),
+                    button(PadButton::DPadRight),
+                    positive(PadAxis::LeftStickX),
+                ],
+            ),
+            (
+                Action::Backspin,
+                vec![
+                    key("Left"),
+                    button(PadButton::DPadLeft),
+                    negative(PadAxis::LeftStickX),
+                ],
+            ),
+            (
+                Action::TogglePreview,
+                vec![key("P"), button(PadButton::North)],
+            ),
+            (
+                Action::SwitchControls,
+                vec![key("C"), button(PadButton::Select)],
+            ),
+            (Action::Pause, vec![key("Escape"), button(PadButton::Start)]),
+            (Action::ToggleDebug, vec![key("F3")]),
+            (Action::ToggleEditor, vec![key("F2")]),
+        ];
+        InputMap {
+            bindings: bindings.into_iter().collect(),
+        }
+    }
+}
+
+impl InputMap {
+    /// Reads the bindings in `path`. Actions missing from the file, such as
+    /// ones added after it was written, keep their default controls, and so
+    /// does `Action::Pause` when the file leaves it without any.
+    pub fn load(path: impl AsRef<Path>) -> Result<InputMap, InputMapError> {
+        let source = fs::read_to_string(path)?;
+        let mut map: InputMap = ron::from_str(&source)?;
+        let defaults = InputMap::default();
+        for (action, controls) in &defaults.bindings {
+            map.bindings
+                .entry(*action)
+                .or_insert_with(|| controls.clone());
+        }
+        if map.controls(Action::Pause).next().is_none() {
+            let pause = defaults.bindings[&Action::Pause].clone();
+            map.bindings.insert(Action::Pause, pause);
+        }
+        return Ok(map);
+    }
+
+    /// Writes the bindings to `path`, creating its directory if needed.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputMapError> {
+        let path = path.as_ref();
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let config = ron::ser::PrettyConfig::new().struct_names(true);
+        fs::write(path, ron::ser::to_string_pretty(self, config)?)?;
+        return Ok(());
+    }
+
+    /// Every control bound to `action`.
+    pub fn controls(&self, action: Action) -> impl Iterator<Item = &Control> {
+        return self.bindings.get(&action).into_iter().flatten();
+    }
+
+    /// Every action `control` triggers.
+    pub fn actions(&self, control: &Control) -> Vec<Action> {
+        return self
+            .bindings
+            .iter()
+            .filter(|(_, controls)| controls.contains(control))
+            .map(|(action, _)| *action)
+            .collect();
+    }
+
+    /// Actions other than `action` that `control` is already bound to.
+    pub fn conflicts_with(&self, action: Action, control: &Control) -> Vec<Action> {
+        let mut actions = self.actions(control);
+        actions.retain(|&other| other != action);
+        return actions;
+    }
+
+    /// Controls bound to more than one action, with those actions.
+    pub fn conflicts(&self) -> Vec<(Control, Vec<Action>)> {
+        let mut conflicts: Vec<(Control, Vec<Action>)> = Vec::new();
+        for control in self.bindings.values().flatten() {
+            let actions = self.actions(control);
+            if actions.len() > 1 && !conflicts.iter().any(|(seen, _)| seen == control) {
+                conflicts.push((control.clone(), actions));
+            }
+        }
+        return conflicts;
+    }
+
+    /// Actions no control triggers.
+    pub fn unbound(&self) -> Vec<Action> {
+        return Action::ALL
+            .into_iter()
+            .filter(|&action| self.controls(action).next().is_none())
+            .collect();
+    }
+
+    /// Whether `control` is the only control of `Action::Pause`. Pause
+    /// opens the menu leading to the controls screen, so it always keeps
+    /// one.
+    pub fn is_last_pause_control(&self, control: &Control) -> bool {
+        let mut controls = self.controls(Action::Pause);
+        return controls.next() == Some(control) && controls.next().is_none();
+    }
+
+    /// Binds `control` to `action`, taking it away from any other action
+    /// except `Action::Pause` when it is the last control of Pause.
+    pub fn bind(&mut self, action: Action, control: Control) {
+        let keep_pause = self.is_last_pause_control(&control);
+        for (&bound_action, controls) in self.bindings.iter_mut() {
+            if !(keep_pause && bound_action == Action::Pause) {
+                controls.retain(|bound| bound != &control);
+            }
+        }
+        let controls = self.bindings.entry(action).or_default();
+        if !controls.contains(&control) {
+            controls.push(control);
+        }
+    }
+
+    /// Takes `control` away from `action`, unless it is the last control of
+    /// `Action::Pause`.
+    pub fn unbind(&mut self, action: Action, control: &Control) {
+        if action == Action::Pause && self.is_last_pause_control(control) {
+            return;
+        }
+        if let Some(controls) = self.bindings.get_mut(&action) {
+            controls.retain(|bound| bound != control);
+        }
+    }
+
+    /// Name of the first control of `action`, for prompts.
+    pub fn label(&self, action: Action) -> String {
+        return match self.controls(action).next() {
+            Some(control) => control.to_string(),
+            None => String::from("(unbound)"),
+        };
+    }
+}
+
+/// Rescales an analog value so the deadzone reads as 0.0 and full
+/// deflection as 1.0.
+fn without_deadzone(value: f32) -> f32 {
+    return ((value - DEADZONE) / (1.0 - DEADZONE)).clamp(0.0, 1.0);
+}
+
+/// Actions as the game sees them, built up from input events. Presses and
+/// releases are remembered until the next frame starts.
+#[derive(Debug, Clone, Default)]
+pub struct ActionState {
+    /// Current value of each control that has reported one, from 0.0 to
+    /// 1.0.
+    controls: HashMap<Control, f32>,
+    down: HashSet<Action>,
+    pressed: HashSet<Action>,
+    released: HashSet<Action>,
+    /// Actions last pressed with an analog control.
+    analog: HashSet<Action>,
+}
+
+impl ActionState {
+    /// Forgets the presses and releases of the previous frame.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+    }
+
+    pub fn handle(&mut self, map: &InputMap, event: &InputEvent) {
+        let changed = match event {
+            InputEvent::Pressed(control) => vec![(control.clone(), 1.0)],
+            InputEvent::Released(control) => vec![(control.clone(), 0.0)],
+            InputEvent::Axis(axis, value) => vec![
+                (
+                    Control::Axis(*axis, AxisDirection::Positive),
+                    without_deadzone(*value),
+                ),
+                (
+                    Control::Axis(*axis, AxisDirection::Negative),
+                    without_deadzone(-*value),
+                ),
+            ],
+        };
+        for (control, value) in changed {
+            self.controls.insert(control.clone(), value);
+            for action in map.actions(&control) {
+                self.update_action(map, action);
+            }
+        }
+    }
+
+    fn update_action(&mut self, map: &InputMap, action: Action) {
+        let strongest = map
+            .controls(action)
+            .map(|control| (control, self.controls.get(control).copied().unwrap_or(0.0)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b));
+        let Some((control, value)) = strongest else {
+            return;
+        };
+        if !self.down.contains(&action) && value >= PRESS_THRESHOLD {
+            self.down.insert(action);
+            self.pressed.insert(action);
+            if control.is_analog() {
+                self.analog.insert(action);
+            } else {
+                self.analog.remove(&action);
+            }
+        } else if self.down.contains(&action) && value < RELEASE_THRESHOLD {
+            self.down.remove(&action);
+            self.released.insert(action);
+        }
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        return self.pressed.contains(&action);
+    }
+
+    pub fn just_released(&self, action: Action) -> bool {
+        return self.released.contains(&action);
+    }
+
+    pub fn is_down(&self, action: Action) -> bool {
+        return self.down.contains(&action);
+    }
+
+    /// How far `action` is pushed, from 0.0 to 1.0, by the strongest of its
+    /// controls. Keys and buttons are either 0.0 or 1.0.
+    pub fn value(&self, map: &InputMap, action: Action) -> f32 {
+        return map
+            .controls(action)
+            .filter_map(|control| self.controls.get(control))
+            .fold(0.0, |strongest, &value| f32::max(strongest, value));
+    }
+
+    /// Whether `action` was last pressed with an analog control, such as a
+    /// trigger, rather than a key or button.
+    pub fn is_analog(&self, action: Action) -> bool {
+        return self.analog.contains(&action);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn feed(state: &mut ActionState, map: &InputMap, events: &[InputEvent]) {
+        state.begin_frame();
+        for event in events {
+            state.handle(map, event);
+        }
+    }
+
+    #[test]
+    fn test_keys_and_buttons() {
+        let map = InputMap::default();
+        let mut state = ActionState::default();
+        feed(&mut state, &map, &[InputEvent::Pressed(key("Space"))]);
+        assert!(state.just_pressed(Action::Throw));
+        assert!(state.is_down(Action::Throw));
+        assert_eq!(state.is_analog(Action::Throw), false);
+
+        // Holding a second control of the same action does not press it again.
+        feed(
+            &mut state,
+            &map,
+            &[InputEvent::Pressed(Control::Button(PadButton::South))],
+        );
+        assert_eq!(state.just_pressed(Action::Throw), false);
+        feed(&mut state, &map, &[InputEvent::Released(key("Space"))]);
+        assert!(state.is_down(Action::Throw));
+        feed(
+            &mut state,
+            &map,
+            &[InputEvent::Released(Control::Button(PadButton::South))],
+        );
+        assert!(state.just_released(Action::Throw));
+        assert_eq!(state.is_down(Action::Throw), false);
+
+        // A tap within one frame is still seen.
+        feed(
+            &mut state,
+            &map,
+            &[
+                InputEvent::Pressed(Control::Button(PadButton::East)),
+                InputEvent::Released(Control::Button(PadButton::East)),
+            ],
+        );
+        assert!(state.just_pressed(Action::Reset));
+        assert_eq!(state.is_down(Action::Reset), false);
+    }
+
+    #[test]
+    fn test_trigger_hysteresis() {
+        let map = InputMap::default();
+        let mut state = ActionState::default();
+        let pull = |value| InputEvent::Axis(PadAxis::RightTrigger, value);
+        feed(&mut state, &map, &[pull(0.5)]);
+        assert_eq!(state.is_down(Action::Throw), false);
+        feed(&mut state, &map, &[pull(0.9)]);
+        assert!(state.just_pressed(Action::Throw));
+        assert!(state.is_analog(Action::Throw));
+        assert!((state.value(&map, Action::Throw) - 0.875).abs() < 1e-5);
+
+        // Easing off between the thresholds keeps the throw held.
+        feed(&mut state, &map, &[pull(0.5)]);
+        assert!(state.is_down(Action::Throw));
+        feed(&mut state, &map, &[pull(0.1)]);
+        assert!(state.just_released(Action::Throw));
+        assert_eq!(state.value(&map, Action::Throw), 0.0);
+    }
+
+    #[test]
+    fn test_rebinding() {
+        let mut map = InputMap::default();
+        assert!(map.conflicts().is_empty());
+        assert!(map.unbound().is_empty());
+        assert_eq!(
+            map.conflicts_with(Action::Throw, &key("R")),
+            vec![Action::Reset]
+        );
+        assert!(map.conflicts_with(Action::Reset, &key("R")).is_empty());
+
+        map.bind(Action::Throw, key("R"));
+        assert_eq!(map.actions(&key("R")), vec![Action::Throw]);
+        assert_eq!(map.label(Action::Reset), "Pad East");
+        map.unbind(Action::Reset, &Control::Button(PadButton::East));
+        assert_eq!(map.unbound(), vec![Action::Reset]);
+        assert_eq!(map.label(Action::Reset), "(unbound)");
+
+        map.bindings
+            .get_mut(&Action::Pause)
+            .unwrap()
+            .push(key("Space"));
+        assert_eq!(
+            map.conflicts(),
+            vec![(key("Space"), vec![Action::Throw, Action::Pause])]
+        );
+    }
+
+    #[test]
+    fn test_pause_keeps_a_control() {
+        let mut map = InputMap::default();
+        let start = Control::Button(PadButton::Start);
+        map.unbind(Action::Pause, &key("Escape"));
+        assert!(map.is_last_pause_control(&start));
+        map.unbind(Action::Pause, &start);
+        assert_eq!(map.label(Action::Pause), "Pad Start");
+
+        // Moving it to another action leaves it on Pause, too.
+        map.bind(Action::Throw, start.clone());
+        assert!(map.controls(Action::Pause).any(|control| control == &start));
+        assert!(map.controls(Action::Throw).any(|control| control == &start));
+
+        map.bind(Action::Pause, key("Escape"));
+        map.unbind(Action::Pause, &start);
+        assert_eq!(map.actions(&start), vec![Action::Throw]);
+        assert!(map.is_last_pause_control(&key("Escape")));
+    }
+
+    #[test]
+    fn test_controls_file() {
+        let path = std::env::temp_dir().join("jmk-klapit-test/controls.ron");
+        let mut map = InputMap::default();
+        map.bind(Action::Throw, key("LCtrl"));
+        map.bindings.remove(&Action::ToggleEditor);
+        map.bindings.insert(Action::Pause, vec![]);
+        map.save(&path).unwrap();
+        let loaded = InputMap::load(&path).unwrap();
+        assert_eq!(loaded.label(Action::ToggleEditor), "F2");
+        assert_eq!(loaded.label(Action::Pause), "Escape");
+        assert!(loaded
+            .controls(Action::Throw)
+            .any(|control| control == &key("LCtrl")));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_stick_directions() {
+        let map = InputMap::default();
+        let mut state = ActionState::default();
+        let stick = |value| InputEvent::Axis(PadAxis::LeftStickY, value);
+        feed(&mut state, &map, &[stick(0.1)]);
+        assert_eq!(state.value(&map, Action::AimUp), 0.0);
+        feed(&mut state, &map, &[stick(-1.0)]);
+        assert!(state.is_down(Action::AimDown));
+        assert_eq!(state.value(&map, Action::AimDown), 1.0);
+        assert_eq!(state.is_down(Action::AimUp), false);
+        feed(&mut state, &map, &[stick(0.6)]);
+        assert!(state.just_released(Action::AimDown));
+        assert!(state.value(&map, Action::AimUp) > 0.0);
+    }
+}
@@ -0,0 +1,223 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::barrier::{Barrier, Kiuas};
+use crate::geometry::Rectangle;
+use crate::wind::Wind;
+
+/// Everything a throw collides with, what is drawn around it and where the
+/// thrower stands. Levels are stored as RON files, so the same definition
+/// drives both collision and rendering.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Level {
+    pub name: String,
+    pub arm_start: Vec2,
+    pub kiuas: Kiuas,
+    pub barriers: Vec<Barrier>,
+    #[serde(default)]
+    pub sprites: Vec<Sprite>,
+    /// A throw whose klapi leaves this area is out of bounds.
+    #[serde(default = "default_bounds")]
+    pub bounds: Rectangle,
+    #[serde(default)]
+    pub wind: Wind,
+}
+
+fn default_bounds() -> Rectangle {
+    Rectangle {
+        position: vec2(0.0, 2.0),
+        width: 10.0,
+        height: 9.0,
+        rotation: 0.0,
+    }
+}
+
+/// A static picture placed in the level. `texture` names one of the
+/// textures the game loads at start up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Sprite {
+    pub texture: String,
+    pub position: Vec2,
+    pub size: Vec2,
+    #[serde(default)]
+    pub z_index: i32,
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+    /// The file parsed, but describes a level that cannot be played.
+    Invalid(String),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LevelError::Io(error) => write!(f, "could not read or write level: {}", error),
+            LevelError::Parse(error) => write!(f, "invalid level file: {}", error),
+            LevelError::Serialize(error) => write!(f, "could not serialize level: {}", error),
+            LevelError::Invalid(reason) => write!(f, "invalid level: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for LevelError {}
+
+impl From<std::io::Error> for LevelError {
+    fn from(error: std::io::Error) -> Self {
+        LevelError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for LevelError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        LevelError::Parse(error)
+    }
+}
+
+impl From<ron::Error> for LevelError {
+    fn from(error: ron::Error) -> Self {
+        LevelError::Serialize(error)
+    }
+}
+
+impl Level {
+    pub fn from_ron(source: &str) -> Result<Level, LevelError> {
+        let level: Level = ron::from_str(source)?;
+        level.validate()?;
+        return Ok(level);
+    }
+
+    /// Checks that every barrier and the goal have a positive size, which
+    /// collision detection relies on.
+    fn validate(&self) -> Result<(), LevelError> {
+        let rects = self
+            .all_barriers()
+            .map(|barrier| &barrier.rect)
+            .chain([&self.kiuas.goal]);
+        for rect in rects {
+            if !(rect.width > 0.0 && rect.height > 0.0) {
+                return Err(LevelError::Invalid(format!(
+                    "rectangle at {} is {} by {}",
+                    rect.position, rect.width, rect.height
+                )));
+            }
+        }
+        return Ok(());
+    }
+
+    pub fn to_ron(&self) -> Result<String, LevelError> {
+        let config = ron::ser::PrettyConfig::new().struct_names(true);
+        return Ok(ron::ser::to_string_pretty(self, config)?);
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Level, LevelError> {
+        let source = fs::read_to_string(path)?;
+        return Level::from_ron(&source);
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LevelError> {
+        fs::write(path, self.to_ron()?)?;
+        return Ok(());
+    }
+
+    /// The floor and the kiuas barriers together.
+    pub fn all_barriers(&self) -> impl Iterator<Item = &Barrier> {
+        self.barriers.iter().chain(self.kiuas.barriers.iter())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use super::*;
+
+    pub(crate) fn sauna() -> Level {
+        Level::from_ron(include_str!("../../levels/sauna.ron")).unwrap()
+    }
+
+    #[test]
+    fn test_load_sauna() {
+        let level = sauna();
+        assert_eq!(level.arm_start, vec2(-1.9, -0.45));
+        assert_eq!(level.kiuas.barriers.len(), 2);
+        assert_eq!(level.barriers.len(), 1);
+        assert_eq!(level.sprites.len(), 3);
+        assert!(level.bounds.contains_point(level.arm_start));
+    }
+
+    #[test]
+    fn test_level_round_trip() {
+        let level = sauna();
+        let reloaded = Level::from_ron(&level.to_ron().unwrap()).unwrap();
+        assert_eq!(reloaded.kiuas.goal.position, level.kiuas.goal.position);
+        assert_eq!(reloaded.barriers[0].material, level.barriers[0].material);
+        assert_eq!(reloaded.sprites[1].texture, "body");
+        assert_eq!(reloaded.bounds.width, level.bounds.width);
+    }
+
+    #[test]
+    fn test_load_lakeside() {
+        let level = Level::from_ron(include_str!("../../levels/lakeside.ron")).unwrap();
+        assert!(matches!(level.wind, Wind::Gusting { .. }));
+        assert_eq!(sauna().wind, Wind::Calm);
+        let reloaded = Level::from_ron(&level.to_ron().unwrap()).unwrap();
+        assert_eq!(reloaded.wind, level.wind);
+        assert_eq!(level.barriers[1].material.name, "Water");
+        assert_eq!(level.barriers[0].material.sound.as_deref(), Some("crunch"));
+    }
+
+    #[test]
+    fn test_load_level_without_materials() {
+        let source = r#"Level(
+            name: "Old sauna",
+            arm_start: (-1.9, -0.45),
+            kiuas: Kiuas(
+                barriers: [
+                    Barrier(
+                        bounciness: 0.8,
+                        friction: 0.5,
+                        rect: Rectangle(position: (3.3, 0.0), width: 1.0, height: 0.8, rotation: 0.0),
+                    ),
+                ],
+                goal: Rectangle(position: (3.1, -0.6), width: 0.3, height: 0.3, rotation: 0.0),
+            ),
+            barriers: [
+                Barrier(
+                    bounciness: 0.5,
+                    friction: 0.4,
+                    rect: Rectangle(position: (0.0, -1.4), width: 20.0, height: 0.2, rotation: 0.0),
+                ),
+            ],
+        )"#;
+        let level = Level::from_ron(source).unwrap();
+        let stone = &level.kiuas.barriers[0].material;
+        assert_eq!(stone.restitution, 0.8);
+        assert_eq!(stone.static_friction, 0.5);
+        assert_eq!(stone.dynamic_friction, 0.5);
+        assert_eq!(level.barriers[0].material.restitution, 0.5);
+        let reloaded = Level::from_ron(&level.to_ron().unwrap()).unwrap();
+        assert_eq!(reloaded.barriers[0].material, level.barriers[0].material);
+        let missing = source.replace("bounciness: 0.5,", "");
+        assert!(matches!(
+            Level::from_ron(&missing),
+            Err(LevelError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_level() {
+        let result = Level::from_ron("Level(name: \"Broken\")");
+        assert!(matches!(result, Err(LevelError::Parse(_))));
+        let mut flat = sauna();
+        flat.kiuas.barriers[0].rect.width = 0.0;
+        let result = Level::from_ron(&flat.to_ron().unwrap());
+        assert!(matches!(result, Err(LevelError::Invalid(_))));
+    }
+}
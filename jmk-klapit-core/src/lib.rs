@@ -0,0 +1,22 @@
+//! Geometry, physics and throw simulation of JMK Klapit, without any
+//! rendering, so tools and tests can run throws headless.
+
+pub mod aim;
+pub mod arm;
+pub mod barrier;
+pub mod geometry;
+pub mod highscore;
+pub mod hotseat;
+pub mod input;
+pub mod klapi;
+pub mod level;
+pub mod material;
+pub mod projectile;
+pub mod round;
+pub mod scoring;
+pub mod simulation;
+pub mod wind;
+
+pub trait GameObject<T> {
+    fn update(&self, delta: f32) -> T;
+}
@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+
+/// What a barrier or a projectile is made of. Two materials in contact
+/// decide together how it bounces and slides, and each can make a sound and
+/// a puff of particles when hit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub name: String,
+    /// Share of the approach speed kept when bouncing off.
+    pub restitution: f32,
+    /// Friction a contact has to overcome before it starts sliding.
+    pub static_friction: f32,
+    /// Friction slowing a contact that slides.
+    pub dynamic_friction: f32,
+    /// Names one of the sounds the game loads at start up.
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Names one of the particle effects the game knows.
+    #[serde(default)]
+    pub particles: Option<String>,
+}
+
+/// How two materials in contact behave together.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mix {
+    pub restitution: f32,
+    pub static_friction: f32,
+    pub dynamic_friction: f32,
+}
+
+impl Material {
+    fn new(name: &str, restitution: f32, static_friction: f32, dynamic_friction: f32) -> Material {
+        Material {
+            name: String::from(name),
+            restitution,
+            static_friction,
+            dynamic_friction,
+            sound: None,
+            particles: None,
+        }
+    }
+
+    fn with_effects(self, sound: &str, particles: &str) -> Material {
+        return Material {
+            sound: Some(String::from(sound)),
+            particles: Some(String::from(particles)),
+            ..self
+        };
+    }
+
+    /// Kiuas stones.
+    pub fn stone() -> Material {
+        return Material::new("Stone", 0.8, 0.6, 0.5).with_effects("clack", "dust");
+    }
+
+    /// Benches, floor boards and logs.
+    pub fn wood() -> Material {
+        return Material::new("Wood", 0.6, 0.6, 0.45).with_effects("knock", "chips");
+    }
+
+    /// A bucket of water takes nearly all the speed out of a hit.
+    pub fn water() -> Material {
+        return Material::new("Water", 0.1, 0.3, 0.2).with_effects("splash", "splash");
+    }
+
+    pub fn snow() -> Material {
+        return Material::new("Snow", 0.05, 0.9, 0.7).with_effects("crunch", "snow");
+    }
+
+    /// The soft leaves of a vihta.
+    pub fn leaves() -> Material {
+        return Material::new("Leaves", 0.1, 0.8, 0.6).with_effects("rustle", "leaves");
+    }
+
+    /// Materials offered in the level editor.
+    pub fn presets() -> Vec<Material> {
+        return vec![
+            Material::stone(),
+            Material::wood(),
+            Material::water(),
+            Material::snow(),
+            Material::leaves(),
+        ];
+    }
+
+    /// Contact between `self` and `other`. Each value is the geometric mean
+    /// of the two, so a soft or slippery side is felt whatever hits it.
+    pub fn mix(&self, other: &Material) -> Mix {
+        let mean = |a: f32, b: f32| (a * b).sqrt();
+        return Mix {
+            restitution: mean(self.restitution, other.restitution),
+            static_friction: mean(self.static_friction, other.static_friction),
+            dynamic_friction: mean(self.dynamic_friction, other.dynamic_friction),
+        };
+    }
+}
+
+impl Default for Material {
+    /// Logs are wood.
+    fn default() -> Self {
+        Material::wood()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn test_mix_is_symmetric() {
+        let mix = Material::stone().mix(&Material::wood());
+        assert_eq!(mix, Material::wood().mix(&Material::stone()));
+        assert!((mix.restitution - (0.8_f32 * 0.6).sqrt()).abs() < 1e-6);
+        assert_eq!(
+            Material::wood().mix(&Material::wood()).dynamic_friction,
+            0.45
+        );
+        let snow = Material::stone().mix(&Material::snow());
+        assert!(snow.restitution < mix.restitution);
+        assert!(snow.static_friction > mix.static_friction);
+    }
+}
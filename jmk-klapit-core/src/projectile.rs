@@ -0,0 +1,343 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::material::Material;
+
+/// Something that can be thrown at the kiuas. Projectiles are defined in a
+/// RON catalogue, so new ones need no code.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "ProjectileFields")]
+pub struct Projectile {
+    pub name: String,
+    /// Names one of the textures the game loads at start up.
+    pub sprite: String,
+    pub width: f32,
+    pub height: f32,
+    pub mass: f32,
+    /// Air drag growing with the airspeed, per unit of cross-section.
+    pub linear_drag: f32,
+    /// Air drag growing with the square of the airspeed, per unit of
+    /// cross-section.
+    pub quadratic_drag: f32,
+    pub material: Material,
+    pub max_speed: f32,
+}
+
+/// A projectile as written in a catalogue. Catalogues saved before drag
+/// depended on the cross-section give a single drag per unit of speed, and
+/// those saved before projectiles had materials give a bounciness.
+#[derive(Deserialize)]
+#[serde(rename = "Projectile")]
+struct ProjectileFields {
+    name: String,
+    sprite: String,
+    width: f32,
+    height: f32,
+    mass: f32,
+    #[serde(default, deserialize_with = "present")]
+    linear_drag: Option<f32>,
+    #[serde(default, deserialize_with = "present")]
+    drag: Option<f32>,
+    #[serde(default)]
+    quadratic_drag: f32,
+    #[serde(default, deserialize_with = "present")]
+    material: Option<Material>,
+    #[serde(default, deserialize_with = "present")]
+    bounciness: Option<f32>,
+    #[serde(default = "default_max_speed")]
+    max_speed: f32,
+}
+
+/// Reads a field that is optional in the file but written without `Some`.
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    return T::deserialize(deserializer).map(Some);
+}
+
+impl From<ProjectileFields> for Projectile {
+    /// The old drag is kept for a projectile flying broadside, showing its
+    /// whole width to the air. The old bounciness scaled the bounce of a
+    /// plain klapi, and as contacts mix materials by their geometric mean it
+    /// scales wood's restitution squared.
+    fn from(fields: ProjectileFields) -> Self {
+        let linear_drag = match (fields.linear_drag, fields.drag) {
+            (Some(linear_drag), _) => linear_drag,
+            (None, Some(drag)) => drag / fields.width,
+            (None, None) => 0.0,
+        };
+        let material = match (fields.material, fields.bounciness) {
+            (Some(material), _) => material,
+            (None, Some(bounciness)) => {
+                let wood = Material::wood();
+                Material {
+                    name: String::from("Custom"),
+                    restitution: wood.restitution * bounciness * bounciness,
+                    ..wood
+                }
+            }
+            (None, None) => Material::default(),
+        };
+        return Projectile {
+            name: fields.name,
+            sprite: fields.sprite,
+            width: fields.width,
+            height: fields.height,
+            mass: fields.mass,
+            linear_drag,
+            quadratic_drag: fields.quadratic_drag,
+            material,
+            max_speed: fields.max_speed,
+        };
+    }
+}
+
+fn default_max_speed() -> f32 {
+    10.0
+}
+
+impl Default for Projectile {
+    /// The plain klapi, the same as the first entry of the bundled
+    /// catalogue.
+    fn default() -> Self {
+        Projectile {
+            name: String::from("Klapi"),
+            sprite: String::from("klapi"),
+            width: 0.45,
+            height: 0.15,
+            mass: 2.5,
+            linear_drag: 0.0,
+            quadratic_drag: 0.05,
+            material: Material::default(),
+            max_speed: default_max_speed(),
+        }
+    }
+}
+
+/// Projectiles handed to the players, one after another.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Catalogue {
+    pub projectiles: Vec<Projectile>,
+}
+
+#[derive(Debug)]
+pub enum CatalogueError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    /// A projectile has no size or no mass.
+    Invalid(String),
+}
+
+impl fmt::Display for CatalogueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogueError::Io(error) => write!(f, "could not read projectiles: {}", error),
+            CatalogueError::Parse(error) => write!(f, "invalid projectile file: {}", error),
+            CatalogueError::Invalid(name) => {
+                write!(f, "projectile {} needs a positive size and mass", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CatalogueError {}
+
+impl From<std::io::Error> for CatalogueError {
+    fn from(error: std::io::Error) -> Self {
+        CatalogueError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for CatalogueError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        CatalogueError::Parse(error)
+    }
+}
+
+impl Catalogue {
+    pub fn from_ron(source: &str) -> Result<Catalogue, CatalogueError> {
+        let catalogue: Catalogue = ron::from_str(source)?;
+        let invalid = catalogue.projectiles.iter().find(|projectile| {
+            !(projectile.width > 0.0 && projectile.height > 0.0 && projectile.mass > 0.0)
+        });
+        if let Some(projectile) = invalid {
+            return Err(CatalogueError::Invalid(projectile.name.clone()));
+        }
+        return Ok(catalogue);
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Catalogue, CatalogueError> {
+        let source = fs::read_to_string(path)?;
+        return Catalogue::from_ron(&source);
+    }
+
+    /// Projectile of the throw at index `throw` in a round. The catalogue
+    /// starts over once every projectile has been thrown, and an empty one
+    /// hands out plain klapis.
+    pub fn for_throw(&self, throw: usize) -> Projectile {
+        if self.projectiles.is_empty() {
+            return Projectile::default();
+        }
+        return self.projectiles[throw % self.projectiles.len()].clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use glam::{vec2, Vec2};
+
+    use super::*;
+    use crate::arm::new_arm;
+    use crate::barrier::tests::test_barrier;
+    use crate::geometry::Contact;
+    use crate::GameObject;
+
+    fn catalogue() -> Catalogue {
+        Catalogue::from_ron(include_str!("../../projectiles.ron")).unwrap()
+    }
+
+    fn named(name: &str) -> Projectile {
+        return catalogue()
+            .projectiles
+            .into_iter()
+            .find(|projectile| projectile.name == name)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_load_catalogue() {
+        let catalogue = catalogue();
+        assert_eq!(catalogue.projectiles.len(), 6);
+        assert_eq!(catalogue.for_throw(0), Projectile::default());
+        assert_eq!(catalogue.for_throw(7).name, catalogue.projectiles[1].name);
+        assert_eq!(Catalogue::default().for_throw(3), Projectile::default());
+    }
+
+    #[test]
+    fn test_reject_weightless_projectile() {
+        let source = include_str!("../../projectiles.ron").replacen("mass: 2.5", "mass: 0.0", 1);
+        assert!(matches!(
+            Catalogue::from_ron(&source),
+            Err(CatalogueError::Invalid(name)) if name == "Klapi"
+        ));
+    }
+
+    #[test]
+    fn test_load_projectile_with_old_drag() {
+        let source = r#"Catalogue(
+            projectiles: [
+                Projectile(
+                    name: "Old kindling",
+                    sprite: "kindling",
+                    width: 0.4,
+                    height: 0.06,
+                    mass: 0.6,
+                    drag: 0.3,
+                ),
+            ],
+        )"#;
+        let kindling = &Catalogue::from_ron(source).unwrap().projectiles[0];
+        assert_eq!(kindling.linear_drag, 0.3 / 0.4);
+        assert_eq!(kindling.quadratic_drag, 0.0);
+        let mut klapi = new_arm(vec2(0.0, 0.0))
+            .holding(kindling.clone())
+            .launch_klapi();
+        klapi.rect.rotation = 0.0;
+        klapi.speed = vec2(0.0, -2.0);
+        assert_eq!(klapi.drag_force(Vec2::ZERO), vec2(0.0, 0.3 * 2.0));
+        let reloaded = ron::to_string(&Catalogue {
+            projectiles: vec![kindling.clone()],
+        })
+        .unwrap();
+        assert_eq!(
+            &Catalogue::from_ron(&reloaded).unwrap().projectiles[0],
+            kindling
+        );
+    }
+
+    #[test]
+    fn test_load_projectile_without_material() {
+        let source = r#"Catalogue(
+            projectiles: [
+                Projectile(
+                    name: "Old klapi",
+                    sprite: "klapi",
+                    width: 0.45,
+                    height: 0.15,
+                    mass: 2.5,
+                    bounciness: 1.0,
+                ),
+                Projectile(
+                    name: "Old kindling",
+                    sprite: "kindling",
+                    width: 0.4,
+                    height: 0.06,
+                    mass: 0.6,
+                    bounciness: 1.2,
+                ),
+            ],
+        )"#;
+        let catalogue = Catalogue::from_ron(source).unwrap();
+        let (klapi, kindling) = (&catalogue.projectiles[0], &catalogue.projectiles[1]);
+        assert_eq!(klapi.material.restitution, Material::wood().restitution);
+        assert_eq!(
+            kindling.material.static_friction,
+            Material::wood().static_friction
+        );
+        let stone = Material::stone();
+        let ratio =
+            stone.mix(&kindling.material).restitution / stone.mix(&klapi.material).restitution;
+        assert!((ratio - 1.2).abs() < 1e-5);
+        let reloaded = ron::to_string(&catalogue).unwrap();
+        assert_eq!(
+            Catalogue::from_ron(&reloaded).unwrap().projectiles[1],
+            *kindling
+        );
+        let plain = source.replace("bounciness: 1.0,", "");
+        assert_eq!(
+            Catalogue::from_ron(&plain).unwrap().projectiles[0].material,
+            Material::default()
+        );
+    }
+
+    #[test]
+    fn test_drag_slows_the_throw() {
+        let arm = new_arm(vec2(0.0, 0.0)).posed(45.0);
+        let plain = arm.launch_klapi();
+        let vihta = arm.holding(named("Vihta")).launch_klapi();
+        assert_eq!(plain.speed, vihta.speed);
+        assert_eq!(vihta.rect.width, named("Vihta").width);
+        let (mut plain, mut vihta) = (plain, vihta);
+        for _ in 0..60 {
+            plain = plain.update(1.0 / 120.0);
+            vihta = vihta.update(1.0 / 120.0);
+        }
+        assert!(vihta.speed.x < plain.speed.x);
+        assert!(vihta.rect.position.x < plain.rect.position.x);
+    }
+
+    #[test]
+    fn test_material_changes_the_bounce() {
+        let barrier = test_barrier(vec2(0.0, 0.0), 4.0, 1.0);
+        let contact = Contact {
+            normal: vec2(0.0, 1.0),
+            depth: 0.0,
+            points: vec![vec2(0.0, 0.5)],
+        };
+        let bounce = |name: &str| {
+            let mut klapi = new_arm(vec2(0.0, 0.0)).holding(named(name)).launch_klapi();
+            klapi.rect.position = vec2(0.0, 0.5);
+            klapi.rect.rotation = 0.0;
+            klapi.speed = vec2(0.0, -4.0);
+            return barrier.on_collision(&klapi, &contact).speed.y;
+        };
+        assert!(bounce("Kindling") > bounce("Klapi"));
+        assert!(bounce("Wet log") < bounce("Klapi"));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::geometry::Rectangle;
+use crate::scoring::{score_throw, ScoreBreakdown};
+use crate::simulation::{Flight, ThrowResult};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrowRecord {
+    pub result: ThrowResult,
+    pub points: u32,
+    pub breakdown: ScoreBreakdown,
+}
+
+/// A fixed number of throws and how each of them went.
+#[derive(Debug, Clone)]
+pub struct Round {
+    pub length: u32,
+    pub throws: Vec<ThrowRecord>,
+}
+
+impl Round {
+    pub fn new(length: u32) -> Round {
+        Round {
+            length,
+            throws: Vec::new(),
+        }
+    }
+
+    /// Scores a finished throw at `goal` and adds it to the round.
+    pub fn record(
+        &mut self,
+        result: ThrowResult,
+        flight: &Flight,
+        goal: &Rectangle,
+    ) -> &ThrowRecord {
+        let breakdown = score_throw(result, flight, goal, self.streak());
+        self.throws.push(ThrowRecord {
+            result,
+            points: breakdown.total(),
+            breakdown,
+        });
+        return self.throws.last().expect("a throw was just recorded");
+    }
+
+    /// Goals in a row at the end of the round so far.
+    pub fn streak(&self) -> u32 {
+        let goals = self
+            .throws
+            .iter()
+            .rev()
+            .take_while(|throw| throw.result == ThrowResult::Goal);
+        return goals.count() as u32;
+    }
+
+    pub fn throws_remaining(&self) -> u32 {
+        return self.length.saturating_sub(self.throws.len() as u32);
+    }
+
+    pub fn is_over(&self) -> bool {
+        return self.throws_remaining() == 0;
+    }
+
+    pub fn score(&self) -> u32 {
+        return self.throws.iter().map(|throw| throw.points).sum();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+    use crate::level::tests::sauna;
+    use crate::scoring::tests::test_flight;
+    use crate::scoring::{GOAL_POINTS, MAX_ACCURACY_POINTS, STREAK_POINTS};
+
+    #[test]
+    fn test_round() {
+        let goal = sauna().kiuas.goal;
+        let flight = test_flight(goal.position + vec2(0.0, 0.2));
+        let mut round = Round::new(4);
+        assert_eq!(round.throws_remaining(), 4);
+        let first = round.record(ThrowResult::Goal, &flight, &goal).points;
+        assert_eq!(first, GOAL_POINTS + MAX_ACCURACY_POINTS);
+        let second = round.record(ThrowResult::Goal, &flight, &goal).points;
+        assert_eq!(second, first + STREAK_POINTS);
+        assert_eq!(round.streak(), 2);
+        round.record(ThrowResult::Abandoned, &flight, &goal);
+        assert_eq!(round.streak(), 0);
+        assert_eq!(round.is_over(), false);
+        round.record(ThrowResult::Goal, &flight, &goal);
+        assert_eq!(round.is_over(), true);
+        assert_eq!(round.throws_remaining(), 0);
+        assert_eq!(round.score(), first * 2 + second);
+    }
+}
@@ -0,0 +1,186 @@
+use glam::Vec2;
+
+use crate::geometry::Rectangle;
+use crate::simulation::{Flight, ThrowResult};
+
+/// Points for a klapi that lands in the goal.
+pub const GOAL_POINTS: u32 = 10;
+/// Points for a klapi heading straight through the centre of the goal.
+pub const MAX_ACCURACY_POINTS: u32 = 5;
+/// Points for each kiuas barrier a goal bounced off on the way.
+pub const BANK_SHOT_POINTS: u32 = 5;
+/// Points for each full turn per second a goal spins at, beyond the turning
+/// of the hand that let it go.
+pub const SPIN_POINTS_PER_TURN: u32 = 1;
+pub const MAX_SPIN_POINTS: u32 = 5;
+/// Points for each goal in a row before this one.
+pub const STREAK_POINTS: u32 = 5;
+/// Points for a miss that brushed past the goal.
+pub const MAX_NEAR_MISS_POINTS: u32 = 5;
+/// A miss further than this from touching the goal earns nothing.
+pub const NEAR_MISS_DISTANCE: f32 = 0.75;
+
+/// Where the points of one throw came from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScoreBreakdown {
+    pub goal: u32,
+    pub accuracy: u32,
+    pub bank_shots: u32,
+    pub spin: u32,
+    pub streak: u32,
+    pub near_miss: u32,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> u32 {
+        return self.goal
+            + self.accuracy
+            + self.bank_shots
+            + self.spin
+            + self.streak
+            + self.near_miss;
+    }
+
+    /// The parts that earned points, with their names, for showing the
+    /// breakdown.
+    pub fn parts(&self) -> Vec<(&'static str, u32)> {
+        let parts = [
+            ("Goal", self.goal),
+            ("Accuracy", self.accuracy),
+            ("Bank shots", self.bank_shots),
+            ("Spin", self.spin),
+            ("Streak", self.streak),
+            ("Near miss", self.near_miss),
+        ];
+        return parts
+            .into_iter()
+            .filter(|(_, points)| *points > 0)
+            .collect();
+    }
+}
+
+/// Distance between the centres of the klapi and the goal at which they
+/// just touch, diagonal to diagonal.
+fn reach(flight: &Flight, goal: &Rectangle) -> f32 {
+    let klapi = &flight.klapi.rect;
+    return (goal.width.hypot(goal.height) + klapi.width.hypot(klapi.height)) * 0.5;
+}
+
+/// Scores a decided throw. `streak` is the number of goals in a row right
+/// before it.
+pub fn score_throw(
+    result: ThrowResult,
+    flight: &Flight,
+    goal: &Rectangle,
+    streak: u32,
+) -> ScoreBreakdown {
+    let reach = reach(flight, goal);
+    return match result {
+        ThrowResult::Goal => {
+            // How far from the centre of the goal the klapi was heading.
+            let to_goal = goal.position - flight.klapi.rect.position;
+            let direction = flight.klapi.speed.normalize_or_zero();
+            let offset = if direction == Vec2::ZERO {
+                to_goal.length()
+            } else {
+                direction.perp_dot(to_goal).abs()
+            };
+            let accuracy = (1.0 - offset / reach).clamp(0.0, 1.0);
+            let turns_per_second = (flight.klapi.rotational_speed - flight.hand_spin).abs() / 360.0;
+            let spin = turns_per_second as u32 * SPIN_POINTS_PER_TURN;
+            ScoreBreakdown {
+                goal: GOAL_POINTS,
+                accuracy: (accuracy * MAX_ACCURACY_POINTS as f32).round() as u32,
+                bank_shots: flight.kiuas_bounces * BANK_SHOT_POINTS,
+                spin: spin.min(MAX_SPIN_POINTS),
+                streak: streak * STREAK_POINTS,
+                near_miss: 0,
+            }
+        }
+        ThrowResult::Abandoned => ScoreBreakdown::default(),
+        ThrowResult::CameToRest | ThrowResult::OutOfBounds | ThrowResult::Timeout => {
+            let gap = (flight.closest_goal_distance - reach).max(0.0);
+            let closeness = (1.0 - gap / NEAR_MISS_DISTANCE).max(0.0);
+            ScoreBreakdown {
+                near_miss: (closeness * MAX_NEAR_MISS_POINTS as f32).round() as u32,
+                ..ScoreBreakdown::default()
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+    use crate::arm::new_arm;
+    use crate::klapi::tests::test_klapi;
+    use crate::level::tests::sauna;
+
+    /// A flight that ended at `position`, heading down.
+    pub(crate) fn test_flight(position: Vec2) -> Flight {
+        let mut flight = Flight::new(test_klapi(position, vec2(0.0, -3.0)));
+        flight.closest_goal_distance = position.distance(sauna().kiuas.goal.position);
+        return flight;
+    }
+
+    #[test]
+    fn test_centred_goal() {
+        let goal = sauna().kiuas.goal;
+        let flight = test_flight(goal.position + vec2(0.0, 0.2));
+        let score = score_throw(ThrowResult::Goal, &flight, &goal, 0);
+        assert_eq!(score.goal, GOAL_POINTS);
+        assert_eq!(score.accuracy, MAX_ACCURACY_POINTS);
+        assert_eq!(score.total(), GOAL_POINTS + MAX_ACCURACY_POINTS);
+        assert_eq!(score.parts(), vec![("Goal", 10), ("Accuracy", 5)]);
+    }
+
+    #[test]
+    fn test_style_points() {
+        let goal = sauna().kiuas.goal;
+        let mut flight = test_flight(goal.position + vec2(0.3, 0.2));
+        flight.bounces = 3;
+        flight.kiuas_bounces = 2;
+        flight.klapi.rotational_speed = -800.0;
+        let score = score_throw(ThrowResult::Goal, &flight, &goal, 3);
+        assert!(score.accuracy < MAX_ACCURACY_POINTS);
+        assert_eq!(score.bank_shots, 2 * BANK_SHOT_POINTS);
+        assert_eq!(score.spin, 2 * SPIN_POINTS_PER_TURN);
+        assert_eq!(score.streak, 3 * STREAK_POINTS);
+    }
+
+    #[test]
+    fn test_no_spin_points_for_the_turning_hand() {
+        let goal = sauna().kiuas.goal;
+        let arm = new_arm(vec2(0.0, 0.0)).posed(30.0);
+        assert!(arm.forearm_speed().abs() >= 720.0);
+        let at_goal = |topspin: f32| {
+            let mut flight = Flight::thrown(&arm, topspin);
+            flight.klapi.rect.position = goal.position + vec2(0.0, 0.2);
+            return score_throw(ThrowResult::Goal, &flight, &goal, 0).spin;
+        };
+        assert_eq!(at_goal(0.0), 0);
+        assert_eq!(at_goal(1.0), 2 * SPIN_POINTS_PER_TURN);
+        assert_eq!(at_goal(-1.0), 2 * SPIN_POINTS_PER_TURN);
+    }
+
+    #[test]
+    fn test_near_miss() {
+        let goal = sauna().kiuas.goal;
+        let close = test_flight(goal.position + vec2(-0.6, 0.0));
+        let far = test_flight(goal.position + vec2(-3.0, 0.0));
+        let close_score = score_throw(ThrowResult::CameToRest, &close, &goal, 2);
+        assert!(close_score.near_miss > 0);
+        assert_eq!(close_score.near_miss, close_score.total());
+        assert_eq!(
+            score_throw(ThrowResult::CameToRest, &far, &goal, 0).total(),
+            0
+        );
+        assert_eq!(
+            score_throw(ThrowResult::Abandoned, &close, &goal, 0).total(),
+            0
+        );
+    }
+}
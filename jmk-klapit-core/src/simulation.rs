@@ -0,0 +1,612 @@
+use glam::Vec2;
+
+use crate::aim::spin_klapi;
+use crate::arm::Arm;
+use crate::barrier::Barrier;
+use crate::geometry::Contact;
+use crate::klapi::Klapi;
+use crate::level::Level;
+use crate::material::Material;
+
+/// Length of one physics step in seconds.
+pub const PHYSICS_TIMESTEP: f32 = 1.0 / 120.0;
+/// Most physics steps run in one frame. Time beyond that is dropped so a
+/// long frame slows the game down instead of stalling it.
+pub const MAX_PHYSICS_STEPS: u32 = 8;
+/// Simulated seconds after which a throw that has not scored is given up.
+pub const MAX_THROW_TIME: f32 = 10.0;
+/// A klapi slower than this is considered still.
+pub const REST_SPEED: f32 = 0.15;
+/// A klapi spinning slower than this, in degrees per second, is considered
+/// still.
+pub const REST_ROTATIONAL_SPEED: f32 = 30.0;
+/// Seconds a klapi has to stay still to have come to rest.
+pub const REST_TIME: f32 = 0.5;
+/// Barriers closer to the klapi than this at the start of a step count as
+/// touching it. A touching barrier the klapi moves into by no more than this
+/// during the step is one it rests on, and is not swept.
+const CONTACT_SLOP: f32 = 0.01;
+
+/// Accumulates variable frame times and splits them into fixed steps, so the
+/// simulation gives the same result regardless of frame rate.
+pub struct FixedTimestep {
+    pub step: f32,
+    pub max_steps: u32,
+    accumulator: f32,
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32, max_steps: u32) -> FixedTimestep {
+        FixedTimestep {
+            step,
+            max_steps,
+            accumulator: 0.0,
+        }
+    }
+
+    /// Adds the time of a frame and returns how many steps to simulate.
+    pub fn advance(&mut self, frame_delta: f32) -> u32 {
+        self.accumulator += frame_delta;
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        if self.accumulator >= self.step {
+            self.accumulator %= self.step;
+        }
+        return steps;
+    }
+
+    /// How far between the previous and the current physics state the frame
+    /// is, from 0.0 to 1.0.
+    pub fn alpha(&self) -> f32 {
+        return self.accumulator / self.step;
+    }
+}
+
+pub enum KlapiStep {
+    Flying(Klapi),
+    /// The klapi hit a barrier during the step. The contact is the last one
+    /// resolved, with its normal pointing from the barrier to the klapi, and
+    /// the material is that barrier's. The flag tells whether any of the
+    /// barriers hit was part of the kiuas.
+    Bounced(Klapi, Contact, Material, bool),
+    Goal(Klapi),
+}
+
+/// Moves the klapi one physics step, `time` seconds after release. The path
+/// between the old and the new state is swept, and the first barrier or goal
+/// hit inside the step is resolved there. The rest of that step is not
+/// simulated. Barriers the klapi rests on, such as the floor it lies or
+/// slides on, are left to the contact response so a resting klapi keeps
+/// moving.
+pub fn step_klapi(klapi: &Klapi, level: &Level, time: f32, delta: f32) -> KlapiStep {
+    let wind = level.wind.at(klapi.rect.position, time);
+    let updated_klapi = klapi.update_in(wind, delta);
+    let first_hit = level
+        .all_barriers()
+        .filter(|barrier| !rests_on(klapi, &updated_klapi, barrier))
+        .filter_map(|barrier| klapi.time_of_impact(&updated_klapi, &barrier.rect))
+        .fold(None, |first: Option<f32>, t| {
+            Some(first.map_or(t, |f| f.min(t)))
+        });
+    if let Some(t) = klapi.time_of_impact(&updated_klapi, &level.kiuas.goal) {
+        if first_hit.is_none_or(|hit| t <= hit) {
+            return KlapiStep::Goal(klapi.lerp(&updated_klapi, t));
+        }
+    }
+    let mut resolved = match first_hit {
+        Some(t) => klapi.lerp(&updated_klapi, t),
+        None => updated_klapi,
+    };
+    let mut last_contact = None;
+    let mut hit_kiuas = false;
+    let floor = level.barriers.iter().map(|barrier| (barrier, false));
+    let kiuas = level.kiuas.barriers.iter().map(|barrier| (barrier, true));
+    for (barrier, is_kiuas) in floor.chain(kiuas) {
+        if let Some(contact) = barrier.rect.contact(&resolved.rect) {
+            resolved = barrier.on_collision(&resolved, &contact);
+            last_contact = Some((contact, barrier.material.clone()));
+            hit_kiuas |= is_kiuas;
+        }
+    }
+    return match last_contact {
+        Some((contact, material)) => KlapiStep::Bounced(resolved, contact, material, hit_kiuas),
+        None => KlapiStep::Flying(resolved),
+    };
+}
+
+/// Whether the klapi rests on `barrier` during the step from `klapi` to
+/// `next`: it touches the barrier and does not move into it further than the
+/// contact slop. A klapi grazing a barrier fast is still heading into it.
+fn rests_on(klapi: &Klapi, next: &Klapi, barrier: &Barrier) -> bool {
+    let Some(contact) = barrier.rect.contact(&klapi.rect.expanded(CONTACT_SLOP)) else {
+        return false;
+    };
+    let motion = next.rect.position - klapi.rect.position;
+    return motion.dot(contact.normal) >= -CONTACT_SLOP;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThrowResult {
+    Goal,
+    CameToRest,
+    OutOfBounds,
+    Timeout,
+    /// The player gave up on the throw before it was decided.
+    Abandoned,
+}
+
+/// A released klapi and what has happened to it since release.
+#[derive(Debug, Clone)]
+pub struct Flight {
+    pub klapi: Klapi,
+    /// Degrees per second the hand turned at when it let go. Spin beyond it
+    /// was put on the klapi by the player.
+    pub hand_spin: f32,
+    /// Simulated seconds since release.
+    pub time: f32,
+    /// Seconds the klapi has been still without interruption.
+    pub rest_time: f32,
+    /// The latest barrier contact of the flight.
+    pub last_contact: Option<Contact>,
+    /// Material of the barrier of the latest contact.
+    pub last_material: Option<Material>,
+    /// Whether the klapi touched a barrier in the latest step.
+    pub touching: bool,
+    /// Separate hits on barriers. Staying in contact, like sliding along
+    /// one, counts once.
+    pub bounces: u32,
+    /// Whether the klapi hit a kiuas barrier in the latest step.
+    pub touching_kiuas: bool,
+    /// Separate hits on kiuas barriers, the bank shots of the throw. Hits
+    /// on the floor and the other barriers of the level do not count.
+    pub kiuas_bounces: u32,
+    /// Closest the centre of the klapi has been to the centre of the goal.
+    pub closest_goal_distance: f32,
+    /// Set once the throw is decided. A decided flight no longer moves.
+    pub result: Option<ThrowResult>,
+}
+
+impl Flight {
+    pub fn new(klapi: Klapi) -> Flight {
+        Flight {
+            klapi,
+            hand_spin: 0.0,
+            time: 0.0,
+            rest_time: 0.0,
+            last_contact: None,
+            last_material: None,
+            touching: false,
+            bounces: 0,
+            touching_kiuas: false,
+            kiuas_bounces: 0,
+            closest_goal_distance: f32::INFINITY,
+            result: None,
+        }
+    }
+
+    /// The flight of the klapi let go from `arm` with `topspin`, as in
+    /// `aim::spin_klapi`.
+    pub fn thrown(arm: &Arm, topspin: f32) -> Flight {
+        return Flight {
+            hand_spin: arm.forearm_speed(),
+            ..Flight::new(spin_klapi(&arm.launch_klapi(), topspin))
+        };
+    }
+
+    /// Advances the flight one physics step and decides the throw when the
+    /// klapi reaches the goal, comes to rest, leaves the level bounds or
+    /// `MAX_THROW_TIME` runs out.
+    pub fn step(&self, level: &Level, delta: f32) -> Flight {
+        if self.result.is_some() {
+            return self.clone();
+        }
+        let time = self.time + delta;
+        let mut last_contact = self.last_contact.clone();
+        let mut last_material = self.last_material.clone();
+        let mut touching = false;
+        let mut touching_kiuas = false;
+        let mut result = None;
+        let klapi = match step_klapi(&self.klapi, level, self.time, delta) {
+            KlapiStep::Flying(klapi) => klapi,
+            KlapiStep::Bounced(klapi, contact, material, hit_kiuas) => {
+                last_contact = Some(contact);
+                last_material = Some(material);
+                touching = true;
+                touching_kiuas = hit_kiuas;
+                klapi
+            }
+            KlapiStep::Goal(klapi) => {
+                result = Some(ThrowResult::Goal);
+                klapi
+            }
+        };
+        let bounces = self.bounces + u32::from(touching && !self.touching);
+        let kiuas_bounces = self.kiuas_bounces + u32::from(touching_kiuas && !self.touching_kiuas);
+        let goal_distance = klapi.rect.position.distance(level.kiuas.goal.position);
+        let closest_goal_distance = self.closest_goal_distance.min(goal_distance);
+        let still = klapi.speed.length() < REST_SPEED
+            && klapi.rotational_speed.abs() < REST_ROTATIONAL_SPEED;
+        let rest_time = if still { self.rest_time + delta } else { 0.0 };
+        if result.is_none() {
+            result = if !level.bounds.contains_point(klapi.rect.position) {
+                Some(ThrowResult::OutOfBounds)
+            } else if rest_time >= REST_TIME {
+                Some(ThrowResult::CameToRest)
+            } else if time >= MAX_THROW_TIME {
+                Some(ThrowResult::Timeout)
+            } else {
+                None
+            };
+        }
+        return Flight {
+            klapi,
+            hand_spin: self.hand_spin,
+            time,
+            rest_time,
+            last_contact,
+            last_material,
+            touching,
+            bounces,
+            touching_kiuas,
+            kiuas_bounces,
+            closest_goal_distance,
+            result,
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ThrowOutcome {
+    pub result: ThrowResult,
+    /// State of the klapi when the throw was decided.
+    pub klapi: Klapi,
+    /// Simulated seconds from release until the throw was decided.
+    pub time: f32,
+}
+
+/// Releases a klapi from `arm` as it is posed now and simulates it in fixed
+/// steps until the throw is decided.
+pub fn simulate_throw(arm: &Arm, level: &Level) -> ThrowOutcome {
+    let mut flight = Flight::thrown(arm, 0.0);
+    loop {
+        flight = flight.step(level, PHYSICS_TIMESTEP);
+        if let Some(result) = flight.result {
+            return ThrowOutcome {
+                result,
+                klapi: flight.klapi,
+                time: flight.time,
+            };
+        }
+    }
+}
+
+/// How much of the path of a klapi released now is shown to the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrajectoryPreview {
+    Off,
+    Short,
+    Full,
+}
+
+impl TrajectoryPreview {
+    /// Seconds of flight the preview covers at most.
+    pub fn max_time(self) -> f32 {
+        match self {
+            TrajectoryPreview::Off => 0.0,
+            TrajectoryPreview::Short => 0.25,
+            TrajectoryPreview::Full => MAX_THROW_TIME,
+        }
+    }
+
+    pub fn next(self) -> TrajectoryPreview {
+        match self {
+            TrajectoryPreview::Off => TrajectoryPreview::Short,
+            TrajectoryPreview::Short => TrajectoryPreview::Full,
+            TrajectoryPreview::Full => TrajectoryPreview::Off,
+        }
+    }
+}
+
+/// Positions the klapi would pass through, one per physics step, if it were
+/// released from `arm` now with `topspin`, as in `aim::spin_klapi`. The path
+/// ends at the first bounce or goal, where the klapi leaves the level bounds,
+/// or when the time covered by `preview` runs out.
+pub fn predict_trajectory(
+    arm: &Arm,
+    level: &Level,
+    preview: TrajectoryPreview,
+    topspin: f32,
+) -> Vec<Vec2> {
+    let mut points = Vec::new();
+    if preview == TrajectoryPreview::Off {
+        return points;
+    }
+    let mut klapi = spin_klapi(&arm.launch_klapi(), topspin);
+    let mut time = 0.0;
+    points.push(klapi.rect.position);
+    while time < preview.max_time() {
+        let step = step_klapi(&klapi, level, time, PHYSICS_TIMESTEP);
+        time += PHYSICS_TIMESTEP;
+        match step {
+            KlapiStep::Flying(next) if level.bounds.contains_point(next.rect.position) => {
+                points.push(next.rect.position);
+                klapi = next;
+            }
+            KlapiStep::Flying(next) | KlapiStep::Bounced(next, _, _, _) | KlapiStep::Goal(next) => {
+                points.push(next.rect.position);
+                break;
+            }
+        }
+    }
+    return points;
+}
+
+#[cfg(test)]
+mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+    use crate::arm::{new_arm, Joint};
+    use crate::barrier::tests::test_barrier;
+    use crate::barrier::Kiuas;
+    use crate::geometry::Rectangle;
+    use crate::klapi::tests::test_klapi;
+    use crate::level::tests::sauna;
+    use crate::projectile::Projectile;
+    use crate::wind::Wind;
+    use crate::GameObject;
+
+    #[test]
+    fn test_fixed_timestep() {
+        let mut timestep = FixedTimestep::new(0.25, 8);
+        assert_eq!(timestep.advance(0.125), 0);
+        assert_eq!(timestep.advance(0.5), 2);
+        assert_eq!(timestep.alpha(), 0.5);
+        assert_eq!(timestep.advance(10.0), 8);
+        assert!(timestep.alpha() < 1.0);
+    }
+
+    #[test]
+    fn test_fixed_timestep_is_frame_rate_independent() {
+        let arm = new_arm(vec2(-1.9, -0.45)).posed(-90.0).swing(720.0);
+        let simulate = |frame_delta: f32, frames: u32| {
+            let mut timestep = FixedTimestep::new(PHYSICS_TIMESTEP, MAX_PHYSICS_STEPS);
+            let mut arm = arm.clone();
+            for _ in 0..frames {
+                for _ in 0..timestep.advance(frame_delta) {
+                    arm = arm.update(timestep.step);
+                }
+            }
+            arm
+        };
+        let slow = simulate(1.0 / 30.0, 3);
+        let fast = simulate(1.0 / 165.0, 17);
+        assert_eq!(slow.shoulder.angle, fast.shoulder.angle);
+        assert_eq!(slow.elbow.angle, fast.elbow.angle);
+    }
+
+    /// A level with nothing but `barriers` in it.
+    fn level_with(barriers: Vec<Barrier>) -> Level {
+        Level {
+            name: String::from("Test"),
+            arm_start: vec2(0.0, 0.0),
+            kiuas: Kiuas {
+                barriers: vec![],
+                goal: Rectangle {
+                    position: vec2(10.0, 10.0),
+                    width: 0.3,
+                    height: 0.3,
+                    rotation: 0.0,
+                },
+            },
+            barriers,
+            sprites: vec![],
+            bounds: Rectangle {
+                position: vec2(0.0, 0.0),
+                width: 20.0,
+                height: 20.0,
+                rotation: 0.0,
+            },
+            wind: Wind::Calm,
+        }
+    }
+
+    #[test]
+    fn test_step_klapi_does_not_pass_through_floor() {
+        let level = level_with(vec![test_barrier(vec2(0.0, -1.0), 20.0, 0.2)]);
+        let klapi = test_klapi(vec2(0.0, 0.0), vec2(1.0, -60.0));
+        let result = match step_klapi(&klapi, &level, 0.0, 0.1) {
+            KlapiStep::Bounced(klapi, contact, material, hit_kiuas) => {
+                assert!(contact.normal.y > 0.9);
+                assert_eq!(hit_kiuas, false);
+                assert_eq!(material, level.barriers[0].material);
+                klapi
+            }
+            _ => panic!("klapi should bounce off the floor"),
+        };
+        assert!(result.rect.position.y > -0.9);
+        assert!(result.speed.y > 0.0);
+    }
+
+    #[test]
+    fn test_step_klapi_does_not_graze_through_thin_barrier() {
+        let level = level_with(vec![test_barrier(vec2(0.0, 0.0), 6.0, 0.04)]);
+        // Just above the plank, closer than the contact slop, and heading
+        // down through it at a shallow angle.
+        let klapi = test_klapi(vec2(-2.5, 0.1), vec2(9.5, -1.5));
+        assert!(klapi
+            .rect
+            .expanded(CONTACT_SLOP)
+            .collide(&level.barriers[0].rect));
+        match step_klapi(&klapi, &level, 0.0, 0.2) {
+            KlapiStep::Bounced(klapi, contact, _, _) => {
+                assert!(contact.normal.y > 0.9);
+                assert!(klapi.rect.position.y > 0.0);
+            }
+            _ => panic!("klapi should hit the plank"),
+        }
+    }
+
+    fn released_arm(angle: f32, speed: f32) -> Arm {
+        let arm = new_arm(sauna().arm_start).posed(angle);
+        Arm {
+            shoulder: Joint {
+                speed,
+                ..arm.shoulder
+            },
+            ..arm
+        }
+    }
+
+    #[test]
+    fn test_simulate_throw_goal() {
+        let arm = released_arm(35.0, 425.0);
+        let outcome = simulate_throw(&arm, &sauna());
+        assert_eq!(outcome.result, ThrowResult::Goal);
+        assert!(outcome.klapi.rect.collide(&sauna().kiuas.goal));
+    }
+
+    #[test]
+    fn test_simulate_throw_is_deterministic() {
+        let arm = released_arm(20.0, 300.0);
+        let first = simulate_throw(&arm, &sauna());
+        let second = simulate_throw(&arm, &sauna());
+        assert_eq!(first.result, second.result);
+        assert_eq!(first.time, second.time);
+        assert_eq!(first.klapi.rect.position, second.klapi.rect.position);
+    }
+
+    fn fly(mut flight: Flight, level: &Level) -> Flight {
+        while flight.result.is_none() {
+            flight = flight.step(level, PHYSICS_TIMESTEP);
+        }
+        return flight;
+    }
+
+    #[test]
+    fn test_flight_comes_to_rest_on_floor() {
+        let level = sauna();
+        let klapi = test_klapi(vec2(-1.0, -1.0), vec2(1.0, 0.0));
+        let flight = fly(Flight::new(klapi), &level);
+        assert_eq!(flight.result, Some(ThrowResult::CameToRest));
+        assert!(flight.time < MAX_THROW_TIME);
+        assert!(flight.rest_time >= REST_TIME);
+        assert!(flight.klapi.speed.length() < REST_SPEED);
+    }
+
+    #[test]
+    fn test_only_kiuas_hits_are_bank_shots() {
+        let level = sauna();
+        let slide = fly(
+            Flight::new(test_klapi(vec2(-1.0, -1.0), vec2(1.0, 0.0))),
+            &level,
+        );
+        assert!(slide.bounces > 0);
+        assert_eq!(slide.kiuas_bounces, 0);
+        let mut bank = Flight::new(test_klapi(vec2(2.0, 0.0), vec2(5.0, 0.0)));
+        while bank.bounces == 0 {
+            bank = bank.step(&level, PHYSICS_TIMESTEP);
+        }
+        assert_eq!(bank.kiuas_bounces, 1);
+        assert!(bank.touching_kiuas);
+    }
+
+    #[test]
+    fn test_floor_bounce_next_to_kiuas_is_no_bank_shot() {
+        let mut level = level_with(vec![test_barrier(vec2(0.0, -1.0), 20.0, 0.2)]);
+        // A kiuas wall standing on the floor, its side just within the
+        // contact slop of the falling klapi.
+        level.kiuas.barriers = vec![test_barrier(vec2(1.0, -0.4), 0.2, 1.0)];
+        let klapi = test_klapi(vec2(0.9 - 0.225 - 0.005, -0.5), vec2(0.0, -3.0));
+        assert!(klapi
+            .rect
+            .expanded(CONTACT_SLOP)
+            .collide(&level.kiuas.barriers[0].rect));
+        let mut flight = Flight::new(klapi);
+        while flight.bounces == 0 {
+            flight = flight.step(&level, PHYSICS_TIMESTEP);
+        }
+        assert!(flight.last_contact.unwrap().normal.y > 0.9);
+        assert_eq!(flight.touching_kiuas, false);
+        assert_eq!(flight.kiuas_bounces, 0);
+    }
+
+    #[test]
+    fn test_flight_out_of_bounds() {
+        let level = sauna();
+        let klapi = test_klapi(vec2(4.5, 1.0), vec2(5.0, 0.0));
+        let flight = fly(Flight::new(klapi), &level);
+        assert_eq!(flight.result, Some(ThrowResult::OutOfBounds));
+        assert_eq!(
+            level.bounds.contains_point(flight.klapi.rect.position),
+            false
+        );
+    }
+
+    #[test]
+    fn test_predict_trajectory() {
+        let level = sauna();
+        let arm = released_arm(35.0, 425.0);
+        assert!(predict_trajectory(&arm, &level, TrajectoryPreview::Off, 0.0).is_empty());
+        let short = predict_trajectory(&arm, &level, TrajectoryPreview::Short, 0.0);
+        let full = predict_trajectory(&arm, &level, TrajectoryPreview::Full, 0.0);
+        assert_eq!(short[0], arm.launch_klapi().rect.position);
+        assert!(short.len() < full.len());
+        assert_eq!(short[..], full[..short.len()]);
+        let outcome = simulate_throw(&arm, &level);
+        assert_eq!(*full.last().unwrap(), outcome.klapi.rect.position);
+        // Spin turns the klapi in the air, which changes its drag.
+        let spun = predict_trajectory(&arm, &level, TrajectoryPreview::Full, 1.0);
+        assert_eq!(spun[0], full[0]);
+        assert_ne!(spun[..short.len()], full[..short.len()]);
+    }
+
+    #[test]
+    fn test_predict_trajectory_out_of_bounds() {
+        let level = Level {
+            bounds: Rectangle {
+                position: vec2(0.0, 0.0),
+                width: 4.0,
+                height: 4.0,
+                rotation: 0.0,
+            },
+            ..level_with(vec![])
+        };
+        let arm = released_arm(35.0, 425.0);
+        let path = predict_trajectory(&arm, &level, TrajectoryPreview::Full, 0.0);
+        let (last, inside) = path.split_last().unwrap();
+        assert!(inside
+            .iter()
+            .all(|point| level.bounds.contains_point(*point)));
+        assert_eq!(level.bounds.contains_point(*last), false);
+        let outcome = simulate_throw(&arm, &level);
+        assert_eq!(outcome.result, ThrowResult::OutOfBounds);
+        assert_eq!(*last, outcome.klapi.rect.position);
+    }
+
+    #[test]
+    fn test_headwind_shortens_throw() {
+        let calm = sauna();
+        let windy = Level {
+            wind: Wind::Constant(vec2(-3.0, 0.0)),
+            ..sauna()
+        };
+        let draggy = released_arm(35.0, 425.0);
+        let arm = draggy.holding(Projectile {
+            quadratic_drag: 0.0,
+            ..Projectile::default()
+        });
+        let steps = 60;
+        let drift = |arm: &Arm, level: &Level| {
+            predict_trajectory(arm, level, TrajectoryPreview::Full, 0.0)[steps].x
+        };
+        // Wind moves nothing without air drag.
+        assert_eq!(drift(&arm, &calm), drift(&arm, &windy));
+        assert!(drift(&draggy, &windy) < drift(&draggy, &calm));
+        assert!(drift(&draggy, &calm) < drift(&arm, &calm));
+    }
+}
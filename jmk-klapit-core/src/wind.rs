@@ -0,0 +1,113 @@
+use std::f32::consts::TAU;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Air moving through a level. Indoor levels are calm. Gusts start over with
+/// every throw, so the trajectory preview shows the wind the throw will meet.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Wind {
+    #[default]
+    Calm,
+    /// Blows the same everywhere, all the time.
+    Constant(Vec2),
+    /// Blows at `base`, with a gust of up to `gust` more building up and
+    /// dying down every `period` seconds. Without a positive period the
+    /// gusts never come.
+    Gusting { base: Vec2, gust: Vec2, period: f32 },
+    /// Still at `ground` and blowing harder higher up, reaching `velocity`
+    /// at `ground + height` and staying at that above it. Without a positive
+    /// height it blows at full `velocity` anywhere above `ground`.
+    Rising {
+        velocity: Vec2,
+        ground: f32,
+        height: f32,
+    },
+}
+
+impl Wind {
+    /// Velocity of the air at `position`, `time` seconds after release.
+    pub fn at(&self, position: Vec2, time: f32) -> Vec2 {
+        match *self {
+            Wind::Calm => Vec2::ZERO,
+            Wind::Constant(velocity) => velocity,
+            Wind::Gusting { base, period, .. } if period <= 0.0 => base,
+            Wind::Gusting { base, gust, period } => {
+                let strength = 0.5 - 0.5 * (TAU * time / period).cos();
+                base + gust * strength
+            }
+            Wind::Rising {
+                velocity,
+                ground,
+                height,
+            } if height <= 0.0 => {
+                if position.y > ground {
+                    velocity
+                } else {
+                    Vec2::ZERO
+                }
+            }
+            Wind::Rising {
+                velocity,
+                ground,
+                height,
+            } => velocity * ((position.y - ground) / height).clamp(0.0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use glam::vec2;
+
+    use super::*;
+
+    #[test]
+    fn test_gusts() {
+        let wind = Wind::Gusting {
+            base: vec2(1.0, 0.0),
+            gust: vec2(2.0, 0.0),
+            period: 4.0,
+        };
+        assert_eq!(wind.at(vec2(0.0, 0.0), 0.0), vec2(1.0, 0.0));
+        assert!((wind.at(vec2(5.0, 3.0), 2.0) - vec2(3.0, 0.0)).length() < 1e-5);
+        assert!((wind.at(vec2(0.0, 0.0), 4.0) - vec2(1.0, 0.0)).length() < 1e-5);
+    }
+
+    #[test]
+    fn test_wind_rises_with_height() {
+        let wind = Wind::Rising {
+            velocity: vec2(-2.0, 0.0),
+            ground: -1.0,
+            height: 2.0,
+        };
+        assert_eq!(wind.at(vec2(0.0, -1.5), 0.0), Vec2::ZERO);
+        assert_eq!(wind.at(vec2(0.0, 0.0), 0.0), vec2(-1.0, 0.0));
+        assert_eq!(wind.at(vec2(0.0, 5.0), 3.0), vec2(-2.0, 0.0));
+    }
+
+    #[test]
+    fn test_degenerate_wind_stays_finite() {
+        let gusting = Wind::Gusting {
+            base: vec2(1.0, 0.0),
+            gust: vec2(2.0, 0.0),
+            period: 0.0,
+        };
+        assert_eq!(gusting.at(vec2(0.0, 0.0), 0.0), vec2(1.0, 0.0));
+        assert_eq!(gusting.at(vec2(0.0, 0.0), 1.5), vec2(1.0, 0.0));
+        let rising = Wind::Rising {
+            velocity: vec2(-2.0, 0.0),
+            ground: -1.0,
+            height: 0.0,
+        };
+        assert_eq!(rising.at(vec2(0.0, -1.0), 0.0), Vec2::ZERO);
+        assert_eq!(rising.at(vec2(0.0, -0.5), 0.0), vec2(-2.0, 0.0));
+        let sinking = Wind::Rising {
+            velocity: vec2(-2.0, 0.0),
+            ground: -1.0,
+            height: -1.0,
+        };
+        assert!(sinking.at(vec2(0.0, -1.5), 0.0).is_finite());
+    }
+}
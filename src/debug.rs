@@ -0,0 +1,91 @@
+use comfy::*;
+use jmk_klapit_core::geometry::Polygon;
+use jmk_klapit_core::level::Level;
+
+use crate::GamePhase;
+
+/// Drawn above everything else in the level.
+const DEBUG_Z_INDEX: i32 = 30;
+/// World units one newton of force is drawn as.
+const FORCE_SCALE: f32 = 0.02;
+/// World units one unit of speed is drawn as.
+const VELOCITY_SCALE: f32 = 0.1;
+
+pub fn draw_polygon(polygon: &Polygon, color: Color) {
+    for line in &polygon.vertices {
+        draw_line(line.start, line.end, 0.015, color, DEBUG_Z_INDEX);
+    }
+}
+
+fn draw_vector(start: Vec2, vector: Vec2, color: Color) {
+    if vector.length() > f32::EPSILON {
+        draw_arrow_pro(
+            start,
+            start + vector,
+            0.015,
+            color,
+            DEBUG_Z_INDEX,
+            0.06,
+            0.15 * PI,
+        );
+    }
+}
+
+/// Collision shapes, the arm pivot, klapi velocity and forces, and the
+/// contact points and normals of the last collision.
+pub fn draw_debug_overlay(phase: &GamePhase, level: &Level) {
+    for barrier in level.all_barriers() {
+        draw_polygon(&barrier.rect.to_poly(), RED);
+    }
+    draw_polygon(&level.kiuas.goal.to_poly(), GREEN);
+
+    let arm = phase.arm();
+    draw_polygon(&arm.upper_arm_rect.to_poly(), SKYBLUE);
+    draw_polygon(&arm.forearm_rect.to_poly(), SKYBLUE);
+    draw_polygon(&arm.hand_rect.to_poly(), BLUE);
+    draw_circle(arm.pivot_location, 0.03, BLUE, DEBUG_Z_INDEX);
+    draw_circle(arm.elbow_position(), 0.03, BLUE, DEBUG_Z_INDEX);
+    draw_vector(
+        arm.hand_position(),
+        arm.hand_velocity() * VELOCITY_SCALE,
+        WHITE,
+    );
+
+    let flight = phase.flight();
+    let last_contact = flight.and_then(|flight| flight.last_contact.as_ref());
+    if let Some(klapi) = flight.map(|flight| &flight.klapi) {
+        let position = klapi.rect.position;
+        draw_polygon(&klapi.rect.to_poly(), YELLOW);
+        draw_vector(position, klapi.speed * VELOCITY_SCALE, WHITE);
+        for force in &klapi.forces {
+            draw_vector(position, *force * FORCE_SCALE, ORANGE);
+        }
+    }
+
+    if let Some(contact) = last_contact {
+        for point in &contact.points {
+            draw_circle(*point, 0.025, PINK, DEBUG_Z_INDEX);
+            draw_vector(*point, contact.normal * 0.2, PINK);
+        }
+    }
+
+    egui::Window::new("Debug")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(0.0, 0.0))
+        .show(egui(), |ui| {
+            if let Some(flight) = flight {
+                let klapi = &flight.klapi;
+                ui.label(format!("time: {:.2}", flight.time));
+                ui.label(format!("rest time: {:.2}", flight.rest_time));
+                let position = klapi.rect.position;
+                ui.label(format!("position: {:.3}, {:.3}", position.x, position.y));
+                ui.label(format!("rotation: {:.1}", klapi.rect.rotation));
+                ui.label(format!("speed: {:.3}, {:.3}", klapi.speed.x, klapi.speed.y));
+                ui.label(format!("rotational speed: {:.1}", klapi.rotational_speed));
+            }
+            if let Some(contact) = last_contact {
+                let normal = contact.normal;
+                ui.label(format!("normal: {:.3}, {:.3}", normal.x, normal.y));
+                ui.label(format!("depth: {:.4}", contact.depth));
+            }
+        });
+}
@@ -0,0 +1,355 @@
+use comfy::*;
+use jmk_klapit_core::barrier::Barrier;
+use jmk_klapit_core::geometry::Rectangle;
+use jmk_klapit_core::level::{Level, Sprite};
+use jmk_klapit_core::material::Material;
+
+use crate::debug::draw_polygon;
+
+/// Part of the level picked in the editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Barrier(usize),
+    KiuasBarrier(usize),
+    Goal,
+    Sprite(usize),
+}
+
+/// What the next click in the world places.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Barrier,
+    KiuasBarrier,
+    Sprite,
+}
+
+/// Changes to the level the game has to react to.
+pub enum EditorAction {
+    None,
+    TestThrow,
+    ArmMoved,
+}
+
+pub struct Editor {
+    pub level_path: String,
+    pub selected: Option<Selection>,
+    pub placing: Option<Placement>,
+    drag_offset: Option<Vec2>,
+    status: String,
+}
+
+fn sprite_rect(sprite: &Sprite) -> Rectangle {
+    Rectangle {
+        position: sprite.position,
+        width: sprite.size.x,
+        height: sprite.size.y,
+        rotation: 0.0,
+    }
+}
+
+fn selection_rect(level: &Level, selection: Selection) -> Option<Rectangle> {
+    match selection {
+        Selection::Barrier(index) => level.barriers.get(index).map(|b| b.rect.clone()),
+        Selection::KiuasBarrier(index) => level.kiuas.barriers.get(index).map(|b| b.rect.clone()),
+        Selection::Goal => Some(level.kiuas.goal.clone()),
+        Selection::Sprite(index) => level.sprites.get(index).map(sprite_rect),
+    }
+}
+
+fn set_position(level: &mut Level, selection: Selection, position: Vec2) {
+    match selection {
+        Selection::Barrier(index) => level.barriers[index].rect.position = position,
+        Selection::KiuasBarrier(index) => level.kiuas.barriers[index].rect.position = position,
+        Selection::Goal => level.kiuas.goal.position = position,
+        Selection::Sprite(index) => level.sprites[index].position = position,
+    }
+}
+
+fn rect_mut(level: &mut Level, selection: Selection) -> Option<&mut Rectangle> {
+    match selection {
+        Selection::Barrier(index) => Some(&mut level.barriers[index].rect),
+        Selection::KiuasBarrier(index) => Some(&mut level.kiuas.barriers[index].rect),
+        Selection::Goal => Some(&mut level.kiuas.goal),
+        Selection::Sprite(_) => None,
+    }
+}
+
+fn barrier_mut(level: &mut Level, selection: Selection) -> Option<&mut Barrier> {
+    match selection {
+        Selection::Barrier(index) => Some(&mut level.barriers[index]),
+        Selection::KiuasBarrier(index) => Some(&mut level.kiuas.barriers[index]),
+        _ => None,
+    }
+}
+
+/// Topmost part of the level under `point`.
+fn pick(level: &Level, point: Vec2) -> Option<Selection> {
+    if level.kiuas.goal.contains_point(point) {
+        return Some(Selection::Goal);
+    }
+    let kiuas_barrier = level
+        .kiuas
+        .barriers
+        .iter()
+        .position(|barrier| barrier.rect.contains_point(point));
+    if let Some(index) = kiuas_barrier {
+        return Some(Selection::KiuasBarrier(index));
+    }
+    let barrier = level
+        .barriers
+        .iter()
+        .position(|barrier| barrier.rect.contains_point(point));
+    if let Some(index) = barrier {
+        return Some(Selection::Barrier(index));
+    }
+    return level
+        .sprites
+        .iter()
+        .rposition(|sprite| sprite_rect(sprite).contains_point(point))
+        .map(Selection::Sprite);
+}
+
+fn new_barrier(position: Vec2) -> Barrier {
+    Barrier {
+        material: Material::wood(),
+        rect: Rectangle {
+            position,
+            width: 0.5,
+            height: 0.2,
+            rotation: 0.0,
+        },
+    }
+}
+
+impl Editor {
+    pub fn new(level_path: String) -> Editor {
+        Editor {
+            level_path,
+            selected: None,
+            placing: None,
+            drag_offset: None,
+            status: String::new(),
+        }
+    }
+
+    pub fn update(&mut self, level: &mut Level) -> EditorAction {
+        if !egui().is_pointer_over_area() {
+            self.handle_mouse(level);
+        }
+        self.draw(level);
+        return self.draw_panel(level);
+    }
+
+    fn handle_mouse(&mut self, level: &mut Level) {
+        let mouse = mouse_world();
+        if is_mouse_button_pressed(MouseButton::Left) {
+            self.selected = match self.placing.take() {
+                Some(Placement::Barrier) => {
+                    level.barriers.push(new_barrier(mouse));
+                    Some(Selection::Barrier(level.barriers.len() - 1))
+                }
+                Some(Placement::KiuasBarrier) => {
+                    level.kiuas.barriers.push(new_barrier(mouse));
+                    Some(Selection::KiuasBarrier(level.kiuas.barriers.len() - 1))
+                }
+                Some(Placement::Sprite) => {
+                    level.sprites.push(Sprite {
+                        texture: String::from("klapi"),
+                        position: mouse,
+                        size: vec2(0.45, 0.15),
+                        z_index: 2,
+                    });
+                    Some(Selection::Sprite(level.sprites.len() - 1))
+                }
+                None => pick(level, mouse),
+            };
+            self.drag_offset = self
+                .selected
+                .and_then(|selection| selection_rect(level, selection))
+                .map(|rect| rect.position - mouse);
+        }
+        if is_mouse_button_released(MouseButton::Left) {
+            self.drag_offset = None;
+        }
+        if let (Some(selection), Some(offset)) = (self.selected, self.drag_offset) {
+            if is_mouse_button_down(MouseButton::Left) {
+                set_position(level, selection, mouse + offset);
+            }
+        }
+        let (_, wheel) = mouse_wheel();
+        if let Some(rect) = self.selected.and_then(|s| rect_mut(level, s)) {
+            rect.rotation += wheel * 5.0;
+        }
+    }
+
+    fn draw(&self, level: &Level) {
+        for (index, sprite) in level.sprites.iter().enumerate() {
+            let color = if self.selected == Some(Selection::Sprite(index)) {
+                YELLOW
+            } else {
+                GRAY.alpha(0.5)
+            };
+            draw_polygon(&sprite_rect(sprite).to_poly(), color);
+        }
+        for (index, barrier) in level.barriers.iter().enumerate() {
+            let selected = self.selected == Some(Selection::Barrier(index));
+            draw_polygon(&barrier.rect.to_poly(), if selected { YELLOW } else { RED });
+        }
+        for (index, barrier) in level.kiuas.barriers.iter().enumerate() {
+            let selected = self.selected == Some(Selection::KiuasBarrier(index));
+            draw_polygon(
+                &barrier.rect.to_poly(),
+                if selected { YELLOW } else { ORANGE },
+            );
+        }
+        let goal_selected = self.selected == Some(Selection::Goal);
+        draw_polygon(
+            &level.kiuas.goal.to_poly(),
+            if goal_selected { YELLOW } else { GREEN },
+        );
+        draw_circle(level.arm_start, 0.05, BLUE, 20);
+    }
+
+    fn draw_panel(&mut self, level: &mut Level) -> EditorAction {
+        let mut action = EditorAction::None;
+        egui::Window::new("Editor")
+            .anchor(egui::Align2::RIGHT_TOP, egui::vec2(0.0, 0.0))
+            .show(egui(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("File");
+                    ui.text_edit_singleline(&mut self.level_path);
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save").clicked() {
+                        self.status = match level.save(&self.level_path) {
+                            Ok(()) => format!("Saved {}", self.level_path),
+                            Err(error) => error.to_string(),
+                        };
+                    }
+                    if ui.button("Load").clicked() {
+                        match Level::load(&self.level_path) {
+                            Ok(loaded) => {
+                                *level = loaded;
+                                self.selected = None;
+                                self.status = format!("Loaded {}", self.level_path);
+                                action = EditorAction::ArmMoved;
+                            }
+                            Err(error) => self.status = error.to_string(),
+                        }
+                    }
+                    if ui.button("Test throw").clicked() {
+                        action = EditorAction::TestThrow;
+                    }
+                });
+                if !self.status.is_empty() {
+                    ui.label(&self.status);
+                }
+                ui.separator();
+                ui.label("Click to place");
+                ui.horizontal(|ui| {
+                    if ui.button("Barrier").clicked() {
+                        self.placing = Some(Placement::Barrier);
+                    }
+                    if ui.button("Kiuas barrier").clicked() {
+                        self.placing = Some(Placement::KiuasBarrier);
+                    }
+                    if ui.button("Sprite").clicked() {
+                        self.placing = Some(Placement::Sprite);
+                    }
+                });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Arm start");
+                    let x = ui.add(egui::DragValue::new(&mut level.arm_start.x).speed(0.01));
+                    let y = ui.add(egui::DragValue::new(&mut level.arm_start.y).speed(0.01));
+                    if x.changed() || y.changed() {
+                        action = EditorAction::ArmMoved;
+                    }
+                });
+                if let Some(selection) = self.selected {
+                    ui.separator();
+                    self.draw_selection(ui, level, selection);
+                }
+            });
+        return action;
+    }
+
+    fn draw_selection(&mut self, ui: &mut egui::Ui, level: &mut Level, selection: Selection) {
+        ui.label(format!("{:?}", selection));
+        if let Some(rect) = rect_mut(level, selection) {
+            ui.horizontal(|ui| {
+                ui.label("Position");
+                ui.add(egui::DragValue::new(&mut rect.position.x).speed(0.01));
+                ui.add(egui::DragValue::new(&mut rect.position.y).speed(0.01));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Size");
+                ui.add(
+                    egui::DragValue::new(&mut rect.width)
+                        .speed(0.01)
+                        .clamp_range(0.01..=20.0),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut rect.height)
+                        .speed(0.01)
+                        .clamp_range(0.01..=20.0),
+                );
+            });
+            ui.add(egui::Slider::new(&mut rect.rotation, -180.0..=180.0).text("rotation"));
+        }
+        if let Some(barrier) = barrier_mut(level, selection) {
+            let material = &mut barrier.material;
+            egui::ComboBox::from_label("material")
+                .selected_text(&material.name)
+                .show_ui(ui, |ui| {
+                    for preset in Material::presets() {
+                        let name = preset.name.clone();
+                        ui.selectable_value(material, preset, name);
+                    }
+                });
+            ui.add(egui::Slider::new(&mut material.restitution, 0.0..=1.0).text("restitution"));
+            ui.add(
+                egui::Slider::new(&mut material.static_friction, 0.0..=1.5).text("static friction"),
+            );
+            ui.add(
+                egui::Slider::new(&mut material.dynamic_friction, 0.0..=1.5)
+                    .text("dynamic friction"),
+            );
+        }
+        if let Selection::Sprite(index) = selection {
+            let sprite = &mut level.sprites[index];
+            ui.horizontal(|ui| {
+                ui.label("Texture");
+                ui.text_edit_singleline(&mut sprite.texture);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Position");
+                ui.add(egui::DragValue::new(&mut sprite.position.x).speed(0.01));
+                ui.add(egui::DragValue::new(&mut sprite.position.y).speed(0.01));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Size");
+                ui.add(egui::DragValue::new(&mut sprite.size.x).speed(0.01));
+                ui.add(egui::DragValue::new(&mut sprite.size.y).speed(0.01));
+            });
+            ui.add(egui::Slider::new(&mut sprite.z_index, 0..=10).text("z index"));
+        }
+        let removable = !matches!(selection, Selection::Goal);
+        if removable && ui.button("Delete").clicked() {
+            match selection {
+                Selection::Barrier(index) => {
+                    level.barriers.remove(index);
+                }
+                Selection::KiuasBarrier(index) => {
+                    level.kiuas.barriers.remove(index);
+                }
+                Selection::Sprite(index) => {
+                    level.sprites.remove(index);
+                }
+                Selection::Goal => {}
+            }
+            self.selected = None;
+            self.drag_offset = None;
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use comfy::*;
+use jmk_klapit_core::material::Material;
+use jmk_klapit_core::simulation::Flight;
+
+/// Sounds materials can name, loaded at start up.
+const SOUNDS: &[(&str, &[u8])] = &[
+    (
+        "clack",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets/clack.wav")),
+    ),
+    (
+        "crunch",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/crunch.wav"
+        )),
+    ),
+    (
+        "knock",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets/knock.wav")),
+    ),
+    (
+        "rustle",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/rustle.wav"
+        )),
+    ),
+    (
+        "splash",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/splash.wav"
+        )),
+    ),
+];
+
+pub fn load_sounds() {
+    for (name, bytes) in SOUNDS {
+        load_sound_from_bytes(name, bytes, StaticSoundSettings::default());
+    }
+}
+
+/// Colour of the particles of an effect a material names.
+fn particle_color(effect: &str) -> Option<Color> {
+    match effect {
+        "chips" => Some(Color::rgb8(176, 128, 78)),
+        "dust" => Some(Color::rgb8(150, 150, 150)),
+        "splash" => Some(Color::rgb8(120, 170, 230)),
+        "snow" => Some(WHITE),
+        "leaves" => Some(Color::rgb8(70, 140, 60)),
+        _ => None,
+    }
+}
+
+fn spawn_effect(effect: &str, position: Vec2, normal: Vec2) {
+    let Some(color) = particle_color(effect) else {
+        return;
+    };
+    spawn_particle_fan(8, normal, 1.2, 0.5..1.5, |particle| Particle {
+        position,
+        size: splat(0.04),
+        color_start: color,
+        color_end: color.alpha(0.0),
+        texture: texture_id("1px"),
+        lifetime_max: 0.4,
+        z_index: 7,
+        ..particle
+    });
+}
+
+/// Plays the sounds and spawns the particles of the barrier and the klapi
+/// that met in the latest bounce of `flight`. Unknown names are skipped, and
+/// an effect both materials share is played once.
+pub fn play_impact(flight: &Flight) {
+    let (Some(contact), Some(barrier)) = (&flight.last_contact, &flight.last_material) else {
+        return;
+    };
+    let point = contact.points.iter().sum::<Vec2>() / contact.points.len() as f32;
+    let materials: [&Material; 2] = [barrier, &flight.klapi.material];
+    let mut sounds: Vec<&str> = materials
+        .iter()
+        .filter_map(|material| material.sound.as_deref())
+        .collect();
+    sounds.dedup();
+    for sound in sounds {
+        if SOUNDS.iter().any(|(name, _)| *name == sound) {
+            play_sound(sound);
+        }
+    }
+    let mut effects: Vec<&str> = materials
+        .iter()
+        .filter_map(|material| material.particles.as_deref())
+        .collect();
+    effects.dedup();
+    for effect in effects {
+        spawn_effect(effect, point, contact.normal);
+    }
+}
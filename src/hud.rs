@@ -0,0 +1,277 @@
+use comfy::*;
+use jmk_klapit_core::aim::{MAX_AIM_ANGLE, MIN_AIM_ANGLE};
+use jmk_klapit_core::highscore::HighScore;
+use jmk_klapit_core::hotseat::{HotSeat, Player, MAX_PLAYERS};
+use jmk_klapit_core::input::{Action, InputMap};
+use jmk_klapit_core::projectile::Projectile;
+use jmk_klapit_core::round::ThrowRecord;
+use jmk_klapit_core::simulation::TrajectoryPreview;
+
+use crate::ControlScheme;
+
+/// What the player picked in the pause menu.
+pub enum PauseAction {
+    None,
+    Resume,
+    Rebind,
+}
+
+fn throw_label(number: usize, throw: &ThrowRecord) -> String {
+    format!("Throw {}: {:?} +{}", number, throw.result, throw.points)
+}
+
+fn player_label(place: usize, player: &Player) -> String {
+    let mut label = format!("{}. {}: {}", place + 1, player.name, player.round.score());
+    if player.tie_break.length > 0 {
+        label += &format!(" (tie-break {})", player.tie_break.score());
+    }
+    return label;
+}
+
+/// The score of the round, the settings and what the current player
+/// throws now and after that.
+pub fn draw_score(
+    hotseat: &HotSeat,
+    preview: TrajectoryPreview,
+    controls: ControlScheme,
+    bindings: &InputMap,
+    in_hand: &Projectile,
+    next: &Projectile,
+) {
+    let multiplayer = hotseat.players.len() > 1;
+    egui::Window::new("Score")
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(0.0, 0.0))
+        .show(egui(), |ui| {
+            if multiplayer {
+                ui.label(format!("TURN: {}", hotseat.current().name));
+            }
+            if hotseat.in_tie_break() {
+                ui.label("TIE-BREAK");
+            }
+            ui.label(format!("SCORE: {}", hotseat.current().round.score()));
+            ui.label(format!(
+                "THROWS LEFT: {}",
+                hotseat.current_round().throws_remaining()
+            ));
+            ui.label(format!("IN HAND: {}", in_hand.name));
+            ui.label(format!("NEXT: {}", next.name));
+            ui.label(format!(
+                "PREVIEW ({}): {:?}",
+                bindings.label(Action::TogglePreview),
+                preview
+            ));
+            ui.label(format!(
+                "CONTROLS ({}): {:?}",
+                bindings.label(Action::SwitchControls),
+                controls
+            ));
+            if multiplayer {
+                ui.separator();
+                for (index, player) in hotseat.players.iter().enumerate() {
+                    let text = format!("{}: {}", player.name, player.round.score());
+                    if index == hotseat.turn {
+                        ui.colored_label(egui::Color32::YELLOW, text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
+            }
+        });
+}
+
+/// Aim and power of the next throw. `power` is `None` until the power meter
+/// starts.
+pub fn draw_gauges(aim_angle: f32, power: Option<f32>) {
+    egui::Window::new("Throw")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(0.0, -150.0))
+        .show(egui(), |ui| {
+            let aim = (aim_angle - MIN_AIM_ANGLE) / (MAX_AIM_ANGLE - MIN_AIM_ANGLE);
+            ui.label("AIM");
+            ui.add(egui::ProgressBar::new(aim).text(format!("{:.0}°", aim_angle)));
+            ui.label("POWER");
+            let power = power.unwrap_or(0.0);
+            ui.add(egui::ProgressBar::new(power).text(format!("{:.0}%", power * 100.0)));
+        });
+}
+
+/// Spin held for the release, from -1.0 for full backspin to 1.0 for full
+/// topspin.
+pub fn draw_spin(topspin: f32) {
+    egui::Window::new("Spin")
+        .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(0.0, -300.0))
+        .show(egui(), |ui| {
+            let name = if topspin > 0.0 { "TOPSPIN" } else { "BACKSPIN" };
+            ui.label(name);
+            let amount = topspin.abs();
+            ui.add(egui::ProgressBar::new(amount).text(format!("{:.0}%", amount * 100.0)));
+        });
+}
+
+/// Wind at the klapi, as an arrow pointing where it blows, scaled so
+/// `FULL_WIND` fills the box.
+pub fn draw_wind(wind: Vec2) {
+    const FULL_WIND: f32 = 4.0;
+    egui::Window::new("Wind")
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(0.0, 0.0))
+        .show(egui(), |ui| {
+            ui.label(format!("WIND: {:.1} m/s", wind.length()));
+            let (rect, _) = ui.allocate_exact_size(egui::vec2(80.0, 80.0), egui::Sense::hover());
+            let strength = (wind.length() / FULL_WIND).min(1.0);
+            let direction = wind.normalize_or_zero();
+            let arrow = egui::vec2(direction.x, -direction.y) * strength * rect.width() * 0.45;
+            let painter = ui.painter();
+            painter.circle_stroke(
+                rect.center(),
+                rect.width() * 0.45,
+                (1.0, egui::Color32::GRAY),
+            );
+            painter.arrow(
+                rect.center() - arrow,
+                arrow * 2.0,
+                (2.0, egui::Color32::LIGHT_BLUE),
+            );
+        });
+}
+
+/// Best scores of the current level and mode, next to the score window.
+/// Rounds of the last game that made the table are highlighted.
+pub fn draw_high_scores(scores: &[&HighScore], new_scores: &[HighScore]) {
+    egui::Window::new("High scores")
+        .anchor(egui::Align2::LEFT_TOP, egui::vec2(170.0, 0.0))
+        .show(egui(), |ui| {
+            if scores.is_empty() {
+                ui.label("No scores yet");
+            }
+            for (place, score) in scores.iter().enumerate() {
+                let text = format!(
+                    "{}. {} {} ({} throws) {}",
+                    place + 1,
+                    score.name,
+                    score.score,
+                    score.throws,
+                    score.date
+                );
+                if new_scores.contains(score) {
+                    ui.colored_label(egui::Color32::YELLOW, text);
+                } else {
+                    ui.label(text);
+                }
+            }
+        });
+}
+
+/// Names of the players of the next game. Returns whether they changed.
+pub fn draw_players(names: &mut Vec<String>) -> bool {
+    let mut changed = false;
+    egui::Window::new("Players")
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(0.0, 0.0))
+        .show(egui(), |ui| {
+            let mut removed = None;
+            for (index, name) in names.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{}.", index + 1));
+                    let edit = egui::TextEdit::singleline(name).desired_width(100.0);
+                    changed |= ui.add(edit).changed();
+                    if ui.button("Remove").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed.filter(|_| names.len() > 1) {
+                names.remove(index);
+                changed = true;
+            }
+            if names.len() < MAX_PLAYERS && ui.button("Add player").clicked() {
+                names.push(format!("Player {}", names.len() + 1));
+                changed = true;
+            }
+        });
+    return changed;
+}
+
+/// Outcome of the throw that just ended.
+pub fn draw_throw_result(hotseat: &HotSeat) {
+    let round = hotseat.current_round();
+    let Some(throw) = round.throws.last() else {
+        return;
+    };
+    egui::Window::new("Result")
+        .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 20.0))
+        .collapsible(false)
+        .show(egui(), |ui| {
+            if hotseat.players.len() > 1 {
+                ui.label(&hotseat.current().name);
+            }
+            ui.heading(throw_label(round.throws.len(), throw));
+            for (name, points) in throw.breakdown.parts() {
+                ui.label(format!("{}: +{}", name, points));
+            }
+        });
+}
+
+/// Asks the players to hand over the arm.
+pub fn draw_next_turn(hotseat: &HotSeat, bindings: &InputMap) {
+    egui::Window::new("Next turn")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .show(egui(), |ui| {
+            if hotseat.in_tie_break() {
+                ui.label("Tie-break throw");
+            }
+            ui.heading(format!("{}'s turn", hotseat.current().name));
+            ui.label(format!(
+                "Press {} when ready",
+                bindings.label(Action::Throw)
+            ));
+        });
+}
+
+/// Every throw of a single player's round, or the standings of all players,
+/// and the final score.
+pub fn draw_game_over(hotseat: &HotSeat, new_high_scores: &[HighScore], bindings: &InputMap) {
+    egui::Window::new("Game over")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .show(egui(), |ui| {
+            let standings = hotseat.standings();
+            if let [player] = standings[..] {
+                for (index, throw) in player.round.throws.iter().enumerate() {
+                    ui.label(throw_label(index + 1, throw));
+                }
+                ui.separator();
+                ui.heading(format!("FINAL SCORE: {}", player.round.score()));
+            } else {
+                ui.heading(format!("{} WINS", standings[0].name));
+                ui.separator();
+                for (place, player) in standings.iter().enumerate() {
+                    ui.label(player_label(place, player));
+                }
+            }
+            if !new_high_scores.is_empty() {
+                ui.label("New high score!");
+            }
+            ui.label(format!(
+                "Press {} to play again",
+                bindings.label(Action::Throw)
+            ));
+        });
+}
+
+pub fn draw_pause(bindings: &InputMap) -> PauseAction {
+    let mut action = PauseAction::None;
+    egui::Window::new("Paused")
+        .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+        .collapsible(false)
+        .show(egui(), |ui| {
+            if ui
+                .button(format!("Resume ({})", bindings.label(Action::Pause)))
+                .clicked()
+            {
+                action = PauseAction::Resume;
+            }
+            if ui.button("Controls").clicked() {
+                action = PauseAction::Rebind;
+            }
+        });
+    return action;
+}
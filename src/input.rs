@@ -0,0 +1,286 @@
+use comfy::*;
+use gilrs::{Axis, Button, EventType, Gilrs};
+use jmk_klapit_core::input::{
+    Action, ActionState, AxisDirection, Control, InputEvent, InputMap, PadAxis, PadButton,
+    PRESS_THRESHOLD,
+};
+
+/// Keys that can be bound to actions. They are bound by their `Debug` names.
+const KEYS: &[KeyCode] = &[
+    KeyCode::A,
+    KeyCode::B,
+    KeyCode::C,
+    KeyCode::D,
+    KeyCode::E,
+    KeyCode::F,
+    KeyCode::G,
+    KeyCode::H,
+    KeyCode::I,
+    KeyCode::J,
+    KeyCode::K,
+    KeyCode::L,
+    KeyCode::M,
+    KeyCode::N,
+    KeyCode::O,
+    KeyCode::P,
+    KeyCode::Q,
+    KeyCode::R,
+    KeyCode::S,
+    KeyCode::T,
+    KeyCode::U,
+    KeyCode::V,
+    KeyCode::W,
+    KeyCode::X,
+    KeyCode::Y,
+    KeyCode::Z,
+    KeyCode::Num0,
+    KeyCode::Num1,
+    KeyCode::Num2,
+    KeyCode::Num3,
+    KeyCode::Num4,
+    KeyCode::Num5,
+    KeyCode::Num6,
+    KeyCode::Num7,
+    KeyCode::Num8,
+    KeyCode::Num9,
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::Up,
+    KeyCode::Down,
+    KeyCode::Left,
+    KeyCode::Right,
+    KeyCode::Space,
+    KeyCode::Return,
+    KeyCode::Escape,
+    KeyCode::Tab,
+    KeyCode::Backspace,
+    KeyCode::LShift,
+    KeyCode::RShift,
+    KeyCode::LCtrl,
+    KeyCode::RCtrl,
+    KeyCode::LAlt,
+    KeyCode::RAlt,
+    KeyCode::Comma,
+    KeyCode::Period,
+    KeyCode::Slash,
+    KeyCode::Semicolon,
+    KeyCode::Minus,
+    KeyCode::Kp0,
+    KeyCode::Kp1,
+    KeyCode::Kp2,
+    KeyCode::Kp3,
+    KeyCode::Kp4,
+    KeyCode::Kp5,
+    KeyCode::Kp6,
+    KeyCode::Kp7,
+    KeyCode::Kp8,
+    KeyCode::Kp9,
+    KeyCode::KpEnter,
+];
+
+const PAD_BUTTONS: &[PadButton] = &[
+    PadButton::South,
+    PadButton::East,
+    PadButton::North,
+    PadButton::West,
+    PadButton::LeftBumper,
+    PadButton::RightBumper,
+    PadButton::Select,
+    PadButton::Start,
+    PadButton::DPadUp,
+    PadButton::DPadDown,
+    PadButton::DPadLeft,
+    PadButton::DPadRight,
+];
+
+const PAD_AXES: &[PadAxis] = &[
+    PadAxis::LeftStickX,
+    PadAxis::LeftStickY,
+    PadAxis::RightStickX,
+    PadAxis::RightStickY,
+    PadAxis::LeftTrigger,
+    PadAxis::RightTrigger,
+];
+
+pub fn key_name(key: KeyCode) -> String {
+    format!("{:?}", key)
+}
+
+pub fn key_code(name: &str) -> Option<KeyCode> {
+    KEYS.iter().copied().find(|&key| key_name(key) == name)
+}
+
+fn pad_button(button: Button) -> Option<PadButton> {
+    match button {
+        Button::South => Some(PadButton::South),
+        Button::East => Some(PadButton::East),
+        Button::North => Some(PadButton::North),
+        Button::West => Some(PadButton::West),
+        Button::LeftTrigger => Some(PadButton::LeftBumper),
+        Button::RightTrigger => Some(PadButton::RightBumper),
+        Button::Select => Some(PadButton::Select),
+        Button::Start => Some(PadButton::Start),
+        Button::DPadUp => Some(PadButton::DPadUp),
+        Button::DPadDown => Some(PadButton::DPadDown),
+        Button::DPadLeft => Some(PadButton::DPadLeft),
+        Button::DPadRight => Some(PadButton::DPadRight),
+        _ => None,
+    }
+}
+
+/// Analog triggers are reported as buttons with a value.
+fn trigger_axis(button: Button) -> Option<PadAxis> {
+    match button {
+        Button::LeftTrigger2 => Some(PadAxis::LeftTrigger),
+        Button::RightTrigger2 => Some(PadAxis::RightTrigger),
+        _ => None,
+    }
+}
+
+fn pad_axis(axis: Axis) -> Option<PadAxis> {
+    match axis {
+        Axis::LeftStickX => Some(PadAxis::LeftStickX),
+        Axis::LeftStickY => Some(PadAxis::LeftStickY),
+        Axis::RightStickX => Some(PadAxis::RightStickX),
+        Axis::RightStickY => Some(PadAxis::RightStickY),
+        _ => None,
+    }
+}
+
+fn pad_event(event: EventType) -> Vec<InputEvent> {
+    match event {
+        EventType::ButtonPressed(button, _) => pad_button(button)
+            .map(|button| InputEvent::Pressed(Control::Button(button)))
+            .into_iter()
+            .collect(),
+        EventType::ButtonReleased(button, _) => pad_button(button)
+            .map(|button| InputEvent::Released(Control::Button(button)))
+            .into_iter()
+            .collect(),
+        EventType::ButtonChanged(button, value, _) => trigger_axis(button)
+            .map(|axis| InputEvent::Axis(axis, value))
+            .into_iter()
+            .collect(),
+        EventType::AxisChanged(axis, value, _) => pad_axis(axis)
+            .map(|axis| InputEvent::Axis(axis, value))
+            .into_iter()
+            .collect(),
+        // Nothing held on a pad that is gone should stay held.
+        EventType::Disconnected => PAD_BUTTONS
+            .iter()
+            .map(|&button| InputEvent::Released(Control::Button(button)))
+            .chain(PAD_AXES.iter().map(|&axis| InputEvent::Axis(axis, 0.0)))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Keyboard and gamepads turned into actions once per frame.
+pub struct Input {
+    pub map: InputMap,
+    pub actions: ActionState,
+    /// `None` when gamepads are not supported on this system.
+    gilrs: Option<Gilrs>,
+    /// Gamepad events of this frame, for binding controls.
+    pad_events: Vec<InputEvent>,
+}
+
+impl Input {
+    pub fn new(map: InputMap) -> Input {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(error) => {
+                eprintln!("Playing without gamepads: {}", error);
+                None
+            }
+        };
+        Input {
+            map,
+            actions: ActionState::default(),
+            gilrs,
+            pad_events: Vec::new(),
+        }
+    }
+
+    /// Reads the controls for this frame. The keyboard is ignored while
+    /// typing into the UI.
+    pub fn poll(&mut self) {
+        self.actions.begin_frame();
+        let typing = egui().wants_keyboard_input();
+        let keys: Vec<Control> = self
+            .map
+            .bindings
+            .values()
+            .flatten()
+            .filter(|control| matches!(control, Control::Key(_)))
+            .cloned()
+            .collect();
+        for control in keys {
+            let Control::Key(name) = &control else {
+                continue;
+            };
+            let Some(key) = key_code(name) else {
+                continue;
+            };
+            if typing {
+                self.handle(InputEvent::Released(control));
+                continue;
+            }
+            if is_key_pressed(key) {
+                self.handle(InputEvent::Pressed(control.clone()));
+            }
+            if is_key_released(key) {
+                self.handle(InputEvent::Released(control));
+            }
+        }
+        self.pad_events.clear();
+        while let Some(event) = self.gilrs.as_mut().and_then(|gilrs| gilrs.next_event()) {
+            for event in pad_event(event.event) {
+                self.handle(event.clone());
+                self.pad_events.push(event);
+            }
+        }
+    }
+
+    /// The first key, button or axis pushed this frame, whether bound or
+    /// not.
+    pub fn captured(&self) -> Option<Control> {
+        if let Some(&key) = KEYS.iter().find(|&&key| is_key_pressed(key)) {
+            return Some(Control::Key(key_name(key)));
+        }
+        return self.pad_events.iter().find_map(|event| match event {
+            InputEvent::Pressed(control) => Some(control.clone()),
+            InputEvent::Axis(axis, value) if value.abs() >= PRESS_THRESHOLD => {
+                let direction = if *value > 0.0 {
+                    AxisDirection::Positive
+                } else {
+                    AxisDirection::Negative
+                };
+                Some(Control::Axis(*axis, direction))
+            }
+            _ => None,
+        });
+    }
+
+    fn handle(&mut self, event: InputEvent) {
+        self.actions.handle(&self.map, &event);
+    }
+
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.actions.just_pressed(action)
+    }
+
+    pub fn value(&self, action: Action) -> f32 {
+        self.actions.value(&self.map, action)
+    }
+}
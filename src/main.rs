@@ -1,13 +1,38 @@
+mod debug;
+mod editor;
+mod effects;
+mod hud;
+mod input;
+mod rebind;
+
+use std::path::PathBuf;
+
 use comfy::*;
+use debug::draw_debug_overlay;
+use editor::{Editor, EditorAction};
+use hud::PauseAction;
+use input::Input;
+use jmk_klapit_core::aim::{
+    self, aim_arm, drag_arm, drag_power, PowerMeter, MAX_AIM_ANGLE, MIN_AIM_ANGLE, MIN_DRAG_LENGTH,
+};
+use jmk_klapit_core::arm::{new_arm, Arm};
+use jmk_klapit_core::geometry::Rectangle;
+use jmk_klapit_core::highscore::{HighScore, HighScores};
+use jmk_klapit_core::hotseat::HotSeat;
+use jmk_klapit_core::input::{Action, InputMap};
+use jmk_klapit_core::klapi::Klapi;
+use jmk_klapit_core::level::Level;
+use jmk_klapit_core::projectile::Catalogue;
+use jmk_klapit_core::simulation::{
+    predict_trajectory, FixedTimestep, Flight, ThrowResult, TrajectoryPreview, MAX_PHYSICS_STEPS,
+    PHYSICS_TIMESTEP,
+};
+use jmk_klapit_core::wind::Wind;
+use jmk_klapit_core::GameObject;
+use rebind::RebindScreen;
 
 comfy_game!("JMK Klapit", KlapiGame, config);
 
-pub fn round_to_precision(number: f32, precision: i32) -> f32 {
-    let val: f32 = number * 10.0_f32.powi(precision);
-    let rounded_val = val.round();
-    return rounded_val / 10.0_f32.powi(precision);
-}
-
 fn config(config: GameConfig) -> GameConfig {
     let mut conf = GameConfig {
         vsync_enabled: false,
@@ -18,421 +43,166 @@ fn config(config: GameConfig) -> GameConfig {
     return conf;
 }
 
-pub trait GameObject<T> {
-    fn update(&self, delta: f32) -> T;
-}
-
-pub struct Line {
-    pub start: Vec2,
-    pub end: Vec2,
-}
-
-impl Line {
-    pub fn collide(&self, other: &Line) -> bool {
-        let denominator = ((self.end.x - self.start.x) * (other.end.y - other.start.y))
-            - ((self.end.y - self.start.y) * (other.end.x - other.start.x));
-        let numerator1 = ((self.start.y - other.start.y) * (other.end.x - other.start.x))
-            - ((self.start.x - other.start.x) * (other.end.y - other.start.y));
-        let numerator2 = ((self.start.y - other.start.y) * (self.end.x - self.start.x))
-            - ((self.start.x - other.start.x) * (self.end.y - self.start.y));
-        if denominator == 0.0 {
-            return numerator1 == 0.0 && numerator2 == 0.0;
-        }
-        let r = numerator1 / denominator;
-        let s = numerator2 / denominator;
-        return (r >= 0.0 && r <= 1.0) && (s >= 0.0 && s <= 1.0);
-    }
-}
-
-pub struct Polygon {
-    pub vertices: Vec<Line>,
+/// Throws each player gets in one round.
+const ROUND_LENGTH: u32 = 10;
+/// Seconds the outcome of a throw is shown before the next one.
+const RESULT_DISPLAY_TIME: f32 = 1.5;
+/// Angular acceleration of the swing when throwing with timed presses.
+const LAUNCH_ACCELERATION: f32 = 720.0;
+/// Degrees per second the aim turns while its key is held, or the stick is
+/// pushed all the way.
+const AIM_SPEED: f32 = 45.0;
+/// How close to the klapi in hand, in world units, a click grabs it.
+const GRAB_RADIUS: f32 = 0.35;
+
+/// How the player throws.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlScheme {
+    /// Space starts charging, starts the swing and lets go early. On a
+    /// gamepad the trigger is held to charge, deeper for more power, and let
+    /// go to swing.
+    Timing,
+    /// The release angle is aimed with the arrow keys, then the power is
+    /// locked from an oscillating meter with Space.
+    AimAndPower,
+    /// The klapi is grabbed with the mouse, pulled back and let go.
+    Drag,
 }
 
-impl Polygon {
-    pub fn collide_point(&self, point: Vec2) -> bool {
-        let mut collision = false;
-        for vertex in &self.vertices {
-            let val = ((vertex.start.y > point.y && vertex.end.y < point.y)
-                || (vertex.start.y < point.y && vertex.end.y > point.y))
-                && (point.x
-                    < (vertex.end.x - vertex.start.x) * (point.y - vertex.start.y)
-                        / (vertex.end.y - vertex.start.y)
-                        + vertex.start.x);
-            if val {
-                collision = !collision
-            }
+impl ControlScheme {
+    pub fn next(&self) -> ControlScheme {
+        match self {
+            ControlScheme::Timing => ControlScheme::AimAndPower,
+            ControlScheme::AimAndPower => ControlScheme::Drag,
+            ControlScheme::Drag => ControlScheme::Timing,
         }
-        collision
     }
-    pub fn collide_line(&self, line: &Line) -> bool {
-        for vertice in &self.vertices {
-            if line.collide(vertice) {
-                return true;
-            }
-        }
-        return false;
-    }
-
-    pub fn collide(&self, other: &Polygon) -> bool {
-        for vertice in &self.vertices {
-            let mut collision = other.collide_line(vertice);
-            if collision {
-                return true;
-            }
-            collision = match other.vertices.first() {
-                Some(other_first) => self.collide_point(other_first.start),
-                None => false,
-            };
-            if collision {
-                return true;
-            }
-        }
-        return false;
-    }
-}
-
-#[derive(Debug)]
-pub struct Rectangle {
-    pub position: Vec2,
-    pub width: f32,
-    pub height: f32,
-    pub rotation: f32,
 }
 
-impl Rectangle {
-    pub fn to_poly(&self) -> Polygon {
-        let tl = self.top_left();
-        let tr = self.top_right();
-        let br = self.bottom_right();
-        let bl = self.bottom_left();
-        return Polygon {
-            vertices: vec![
-                Line { start: tl, end: tr },
-                Line { start: tr, end: br },
-                Line { start: br, end: bl },
-                Line { start: bl, end: tl },
-            ],
-        };
-    }
-
-    pub fn collide(&self, rect: &Rectangle) -> bool {
-        return self.to_poly().collide(&rect.to_poly());
-    }
-
-    pub fn top_left(&self) -> Vec2 {
-        let theta = self.rotation.to_radians();
-        let x = self.position.x
-            - ((self.width * 0.5) * theta.cos())
-            - ((self.height * 0.5) * theta.sin());
-        let y = self.position.y - ((self.width * 0.5) * theta.sin())
-            + ((self.height * 0.5) * theta.cos());
-        return vec2(x, y);
-    }
-
-    pub fn top_right(&self) -> Vec2 {
-        let theta = self.rotation.to_radians();
-        let x = self.position.x + ((self.width * 0.5) * theta.cos())
-            - ((self.height * 0.5) * theta.sin());
-        let y = self.position.y
-            + ((self.width * 0.5) * theta.sin())
-            + ((self.height * 0.5) * theta.cos());
-        return vec2(x, y);
-    }
-
-    pub fn bottom_right(&self) -> Vec2 {
-        let theta = self.rotation.to_radians();
-        let x = self.position.x
-            + ((self.width * 0.5) * theta.cos())
-            + ((self.height * 0.5) * theta.sin());
-        let y = self.position.y + ((self.width * 0.5) * theta.sin())
-            - ((self.height * 0.5) * theta.cos());
-        return vec2(x, y);
-    }
-
-    pub fn bottom_left(&self) -> Vec2 {
-        let theta = self.rotation.to_radians();
-        let x = self.position.x - ((self.width * 0.5) * theta.cos())
-            + ((self.height * 0.5) * theta.sin());
-        let y = self.position.y
-            - ((self.width * 0.5) * theta.sin())
-            - ((self.height * 0.5) * theta.cos());
-        return vec2(x, y);
-    }
-
-    pub fn pivot(&self, pivot_point: Vec2, angle: f32) -> Rectangle {
-        let theta = angle.to_radians();
-        let centered_x = self.position.x - pivot_point.x;
-        let centered_y = self.position.y - pivot_point.y;
-        let d_x = theta.cos() * centered_x - theta.sin() * centered_y;
-        let d_y = theta.sin() * centered_x + theta.cos() * centered_y;
-        let pos_x = pivot_point.x + d_x;
-        let pos_y = pivot_point.y + d_y;
-        let position = vec2(round_to_precision(pos_x, 6), round_to_precision(pos_y, 6));
-        return Rectangle {
-            position,
-            width: self.width,
-            height: self.height,
-            rotation: angle,
-        };
-    }
-
-    pub fn contains_point(&self, point: Vec2) -> bool {
-        self.to_poly().collide_point(point)
-    }
+#[derive(Clone)]
+pub enum GamePhase {
+    Start(Arm),
+    /// The power meter runs until the player locks it.
+    Powering(Arm, PowerMeter),
+    /// The klapi is held with the mouse. Holds the point it was grabbed at.
+    Dragging(Arm, Vec2),
+    Charging(Arm),
+    Launching(Arm),
+    Launched(Arm, Flight),
+    /// The outcome of the last throw is shown. Holds the seconds it has been
+    /// shown so far.
+    Results(Arm, Flight, f32),
+    /// The arm is handed to the next player.
+    NextTurn(Arm),
+    GameOver(Arm),
 }
 
-impl Clone for Rectangle {
-    fn clone(&self) -> Self {
-        Rectangle {
-            position: self.position,
-            width: self.width,
-            height: self.height,
-            rotation: self.rotation,
+impl GamePhase {
+    pub fn arm(&self) -> &Arm {
+        match self {
+            GamePhase::Start(arm)
+            | GamePhase::Powering(arm, _)
+            | GamePhase::Dragging(arm, _)
+            | GamePhase::Charging(arm)
+            | GamePhase::Launching(arm)
+            | GamePhase::Launched(arm, _)
+            | GamePhase::Results(arm, _, _)
+            | GamePhase::NextTurn(arm)
+            | GamePhase::GameOver(arm) => arm,
         }
     }
-}
-
-#[derive(Debug, Clone)]
-pub struct Klapi {
-    pub rect: Rectangle,
-    pub mass: f32,
-    pub forces: Vec<Vec2>,
-    pub speed: Vec2,
-    pub rotational_speed: f32,
-    pub max_speed: f32,
-}
 
-impl Klapi {
-    fn calculate_new_speed(&self, delta: f32) -> Vec2 {
-        let mut x_force_sum = 0.0;
-        let mut y_force_sum = 0.0;
-        for f in &self.forces {
-            x_force_sum = x_force_sum + f.x;
-            y_force_sum = y_force_sum + f.y;
-        }
-        let x_change = x_force_sum * (delta / self.mass);
-        let y_change = y_force_sum * (delta / self.mass);
-        let new_x_speed = (self.speed.x + x_change).min(self.max_speed);
-        let new_y_speed = (self.speed.y + y_change).min(self.max_speed);
-        return vec2(new_x_speed, new_y_speed);
-    }
-
-    fn calculate_new_rect(&self, speed: Vec2, delta: f32) -> Rectangle {
-        let x = self.rect.position.x + speed.x * delta;
-        let y = self.rect.position.y + speed.y * delta;
-        let rotation = self.rect.rotation + self.rotational_speed * delta;
-        let position = vec2(x, y);
-        return Rectangle {
-            height: self.rect.height,
-            width: self.rect.width,
-            rotation,
-            position,
-        };
+    /// Whether the klapi is still in the hand, waiting to be thrown.
+    pub fn holds_klapi(&self) -> bool {
+        matches!(
+            self,
+            GamePhase::Start(_)
+                | GamePhase::Powering(_, _)
+                | GamePhase::Dragging(_, _)
+                | GamePhase::Charging(_)
+                | GamePhase::Launching(_)
+                | GamePhase::NextTurn(_)
+        )
     }
-}
-
-impl GameObject<Klapi> for Klapi {
-    fn update(&self, delta: f32) -> Klapi {
-        let speed = self.calculate_new_speed(delta);
-        let rect = self.calculate_new_rect(speed, delta);
-        return Klapi {
-            rect,
-            speed,
-            rotational_speed: self.rotational_speed,
-            forces: self.forces.clone(),
-            max_speed: self.max_speed,
-            mass: self.mass,
-        };
-    }
-}
 
-#[derive(Clone)]
-pub struct Arm {
-    pub arm_rect: Rectangle,
-    pub hand_rect: Rectangle,
-    pub min_angle: f32,
-    pub max_angle: f32,
-    pub angle: f32,
-    pub radius: f32,
-    pub thrown: bool,
-    pub acceleration: f32,
-    pub speed: f32,
-    pub start_location: Vec2,
-    pub pivot_location: Vec2,
-}
-
-impl Arm {
-    fn get_arm_start_rect(&self) -> Rectangle {
-        let mut rect = self.arm_rect.clone();
-        rect.position = self.start_location.clone();
-        return rect;
-    }
-
-    fn launch_klapi(&self) -> Klapi {
-        let theta = self.angle.to_radians();
-        let frequency = 1.0 / (360.0 / self.speed);
-        let velocity = 2.0 * PI * frequency * self.radius;
-        let x_speed = velocity * theta.cos();
-        let y_speed = 1.0 * velocity * theta.sin();
-        Klapi {
-            rect: Rectangle {
-                position: self.arm_rect.bottom_right(),
-                height: 0.15,
-                width: 0.45,
-                rotation: self.angle,
-            },
-            speed: vec2(x_speed, y_speed),
-            rotational_speed: 0.0,
-            max_speed: 10.0,
-            mass: 2.5,
-            forces: vec![vec2(0.0, -9.81 * 2.5)],
+    pub fn flight(&self) -> Option<&Flight> {
+        match self {
+            GamePhase::Launched(_, flight) | GamePhase::Results(_, flight, _) => Some(flight),
+            _ => None,
         }
     }
 }
 
-impl GameObject<Arm> for Arm {
-    fn update(&self, delta: f32) -> Arm {
-        let speed = self.speed + self.acceleration * delta;
-        let mut angle = self.angle + speed * delta;
-        if angle > self.max_angle {
-            angle = self.max_angle;
-        } else if angle < self.min_angle {
-            angle = self.min_angle
-        }
-        let rect = self.get_arm_start_rect();
-        let arm_rect = rect.pivot(self.pivot_location, angle);
-
-        return Arm {
-            start_location: self.start_location,
-            pivot_location: self.pivot_location,
-            acceleration: self.acceleration,
-            arm_rect,
-            angle,
-            max_angle: self.max_angle,
-            min_angle: self.min_angle,
-            speed,
-            thrown: self.thrown,
-            radius: self.radius,
-            hand_rect: self.hand_rect.clone(),
+fn draw_phase(previous: &GamePhase, current: &GamePhase, alpha: f32) {
+    let same_phase = std::mem::discriminant(previous) == std::mem::discriminant(current);
+    let arm = if same_phase {
+        previous.arm().lerp(current.arm(), alpha)
+    } else {
+        current.arm().clone()
+    };
+    draw_arm(&arm);
+    if let Some(flight) = current.flight() {
+        let klapi = match previous.flight() {
+            Some(previous_flight) => previous_flight.klapi.lerp(&flight.klapi, alpha),
+            None => flight.klapi.clone(),
         };
+        draw_klapi(&klapi, &arm.projectile.sprite);
+    } else if current.holds_klapi() {
+        draw_klapi(&arm.launch_klapi(), &arm.projectile.sprite);
     }
 }
 
-fn new_arm(start_location: Vec2) -> Arm {
-    let height = 0.9;
-    let width = 0.18;
-    let angle = 0.0;
-    let hand_y = start_location.y - height * 0.5;
-    let hand_position = vec2(start_location.x, hand_y);
-    let pivot_y = start_location.y + height * 0.5;
-    let pivot_location = vec2(start_location.x, pivot_y);
-    return Arm {
-        start_location,
-        pivot_location,
-        acceleration: 0.0,
-        arm_rect: Rectangle {
-            height,
-            width,
-            position: start_location,
-            rotation: angle,
-        },
-        angle,
-        max_angle: 90.0,
-        min_angle: -90.0,
-        speed: 720.0,
-        thrown: false,
-        radius: height,
-        hand_rect: Rectangle {
-            position: hand_position,
-            height: width,
-            width,
-            rotation: angle,
-        },
-    };
-}
-
-#[derive(Clone)]
-pub struct Barrier {
-    pub bounciness: f32,
-    pub rect: Rectangle,
+fn draw_klapi(klapi: &Klapi, sprite: &str) {
+    draw_sprite_rot(
+        texture_id(sprite),
+        klapi.rect.position,
+        WHITE,
+        5,
+        klapi.rect.rotation.to_radians(),
+        vec2(klapi.rect.width, klapi.rect.height),
+    );
 }
 
-impl Barrier {
-    fn on_collision(&self, klapi: &Klapi) -> Klapi {
-        let rect = self.move_klapi_out_of(klapi);
-        let velo = (klapi.speed.x.powf(2.0) + klapi.speed.x.powf(2.0)).sqrt();
-        let x_speed = self.bounciness * velo * (0.0_f32).cos();
-        let y_speed = self.bounciness * velo * (0.0_f32).sin();
-        let speed = vec2(x_speed, y_speed);
-        return Klapi {
-            forces: klapi.forces.clone(),
-            rotational_speed: klapi.rotational_speed,
-            speed,
-            rect,
-            mass: klapi.mass,
-            max_speed: klapi.max_speed,
-        };
-    }
-
-    fn move_klapi_out_of(&self, klapi: &Klapi) -> Rectangle {
-        let resolution = 0.01;
-        let mut rect = klapi.rect.clone();
-        while self.rect.collide(&rect) {
-            let old_pos = klapi.rect.position;
-            let sign_x = if klapi.speed.x >= 0.0 {
-                1.0
-            } else {
-                - 1.0
-            };
-            let sign_y = if klapi.speed.y >= 0.0 {
-                1.0
-            } else {
-                -1.0
-            };
-            let x_delta = if (klapi.speed.x * resolution).abs() <= resolution {
-                sign_x * resolution
-            } else {
-                klapi.speed.x * resolution
-            };
-            let y_delta = if (klapi.speed.y * resolution).abs() <= resolution {
-                sign_y * resolution
-            } else {
-                klapi.speed.y * resolution
-            };
-            let x = old_pos.x - x_delta;
-            let y = old_pos.y - y_delta;
-            rect = Rectangle {
-                position: vec2(x, y),
-                width: klapi.rect.width,
-                height: klapi.rect.height,
-                rotation: klapi.rect.rotation,
-            };
-        }
-        return rect;
+/// Draws a dot on every few physics steps of the predicted path.
+fn draw_trajectory(points: &[Vec2]) {
+    for point in points.iter().step_by(4) {
+        draw_circle(*point, 0.02, WHITE.alpha(0.7), 6);
     }
 }
 
-#[derive(Clone)]
-pub struct Kiuas {
-    pub barriers: Vec<Barrier>,
-    pub goal: Rectangle,
-}
+/// Size of the arm texture in pixels. The square at its bottom is the hand,
+/// and the rest is split evenly between the upper arm and the forearm.
+const ARM_TEXTURE_SIZE: IVec2 = IVec2::new(43, 295);
 
-pub enum GamePhase {
-    Start(Arm),
-    Charging(Arm),
-    Launching(Arm),
-    Launched(Arm, Klapi, Kiuas, Vec<Barrier>),
+fn draw_arm_segment(segment: &Rectangle, source_rect: IRect, z_index: i32) {
+    draw_sprite_ex(
+        texture_id("arm"),
+        segment.position,
+        WHITE,
+        z_index,
+        DrawTextureParams {
+            dest_size: Some(Size::world(segment.width, segment.height)),
+            source_rect: Some(source_rect),
+            rotation: segment.rotation.to_radians(),
+            ..Default::default()
+        },
+    );
 }
 
+/// Draws the arm with the hand above the klapi it holds.
 fn draw_arm(arm: &Arm) {
-    draw_sprite_rot(
-        texture_id("arm"),
-        arm.arm_rect.position,
-        WHITE.alpha(1.0),
+    let width = ARM_TEXTURE_SIZE.x;
+    let segment = ivec2(width, (ARM_TEXTURE_SIZE.y - width) / 2);
+    draw_arm_segment(&arm.upper_arm_rect, IRect::new(IVec2::ZERO, segment), 5);
+    draw_arm_segment(
+        &arm.forearm_rect,
+        IRect::new(ivec2(0, segment.y), segment),
         5,
-        arm.angle.to_radians(),
-        vec2(arm.arm_rect.width, arm.arm_rect.height),
+    );
+    draw_arm_segment(
+        &arm.hand_rect,
+        IRect::new(ivec2(0, segment.y * 2), ivec2(width, width)),
+        6,
     );
 }
 
@@ -469,289 +239,639 @@ fn load_textures(context: &mut EngineContext) {
             "/src/assets/klapi.png"
         )),
     );
+    context.load_texture_from_bytes(
+        "kindling",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/kindling.png"
+        )),
+    );
+    context.load_texture_from_bytes(
+        "birch",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets/birch.png")),
+    );
+    context.load_texture_from_bytes(
+        "wet_log",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/wet_log.png"
+        )),
+    );
+    context.load_texture_from_bytes(
+        "ladle",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets/ladle.png")),
+    );
+    context.load_texture_from_bytes(
+        "bucket",
+        include_bytes!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/assets/bucket.png"
+        )),
+    );
+    context.load_texture_from_bytes(
+        "vihta",
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets/vihta.png")),
+    );
 }
 
-fn draw_statics(origin: Vec2) {
-    draw_sprite(
-        texture_id("background"),
-        origin.clone(),
-        WHITE,
-        1,
-        vec2(8.0, 3.5),
-    );
-    let body_loc = vec2(origin.x - 2.0, origin.y - 0.25);
-    draw_sprite(texture_id("body"), body_loc, WHITE, 2, vec2(1.108, 1.8));
-    let kiuas_loc = vec2(origin.x + 3.3, origin.y - 0.1);
-    draw_sprite(texture_id("kiuas"), kiuas_loc, WHITE, 2, vec2(1.35, 1.8));
+fn draw_statics(level: &Level) {
+    for sprite in &level.sprites {
+        draw_sprite(
+            texture_id(&sprite.texture),
+            sprite.position,
+            WHITE,
+            sprite.z_index,
+            sprite.size,
+        );
+    }
+}
+
+/// Level file used when none is given on the command line.
+const DEFAULT_LEVEL_PATH: &str = "levels/sauna.ron";
+/// Copy of the default level built into the binary, so the game starts even
+/// when run away from the repository.
+const BUILT_IN_LEVEL: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/levels/sauna.ron"));
+
+fn level_path() -> String {
+    std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_LEVEL_PATH.to_string())
+}
+
+fn load_level(path: &str) -> Level {
+    match Level::load(path) {
+        Ok(level) => level,
+        Err(error) => {
+            eprintln!("Using the built-in level, {}: {}", path, error);
+            Level::from_ron(BUILT_IN_LEVEL).expect("built-in level is valid")
+        }
+    }
+}
+
+/// Projectiles the players throw, in the order they get them.
+const PROJECTILES_PATH: &str = "projectiles.ron";
+const BUILT_IN_PROJECTILES: &str =
+    include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/projectiles.ron"));
+
+fn load_catalogue() -> Catalogue {
+    match Catalogue::load(PROJECTILES_PATH) {
+        Ok(catalogue) => catalogue,
+        Err(error) => {
+            eprintln!(
+                "Using the built-in projectiles, {}: {}",
+                PROJECTILES_PATH, error
+            );
+            Catalogue::from_ron(BUILT_IN_PROJECTILES).expect("built-in projectiles are valid")
+        }
+    }
+}
+
+/// High scores live in the user's data directory, so they survive updates
+/// and are shared by every copy of the game.
+fn high_scores_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("jmk-klapit")
+        .join("highscores.ron")
+}
+
+fn load_high_scores(path: &PathBuf) -> HighScores {
+    match HighScores::load(path) {
+        Ok(high_scores) => high_scores,
+        Err(error) => {
+            if path.exists() {
+                eprintln!(
+                    "Starting without high scores, {}: {}",
+                    path.display(),
+                    error
+                );
+            }
+            HighScores::default()
+        }
+    }
+}
+
+/// Key bindings live in the user's config directory.
+fn bindings_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("jmk-klapit")
+        .join("controls.ron")
+}
+
+fn load_bindings(path: &PathBuf) -> InputMap {
+    let map = match InputMap::load(path) {
+        Ok(map) => map,
+        Err(error) => {
+            if path.exists() {
+                eprintln!("Using the default controls, {}: {}", path.display(), error);
+            }
+            InputMap::default()
+        }
+    };
+    for (control, actions) in map.conflicts() {
+        eprintln!(
+            "{} is bound to more than one action: {:?}",
+            control, actions
+        );
+    }
+    return map;
+}
+
+/// High scores are kept separately for each trajectory preview setting and
+/// control scheme, as they change how hard the game is. `HighScores::load`
+/// relies on this format to move older modes to `ControlScheme::Timing`.
+fn mode_name(preview: TrajectoryPreview, controls: ControlScheme) -> String {
+    format!("{:?} preview, {:?}", preview, controls)
+}
+
+fn charge(arm: &Arm) -> GamePhase {
+    GamePhase::Charging(arm.wind_up(-60.0))
+}
+
+fn start_launching(arm: &Arm, acceleration: f32) -> GamePhase {
+    GamePhase::Launching(arm.swing(acceleration))
+}
+
+/// Lets go of the klapi with `topspin`, as in `aim::spin_klapi`.
+fn launch(arm: &Arm, topspin: f32) -> GamePhase {
+    GamePhase::Launched(arm.clone(), Flight::thrown(arm, topspin))
 }
 
 pub struct KlapiGame {
+    pub level: Level,
+    pub level_path: String,
+    pub catalogue: Catalogue,
+    pub editor: Option<Editor>,
+    pub preview: TrajectoryPreview,
+    pub controls: ControlScheme,
+    /// Release angle aimed at with `ControlScheme::AimAndPower`.
+    pub aim_angle: f32,
+    /// Power locked from the meter for the current throw.
+    pub power: f32,
+    /// Deepest pull of an analog trigger while charging, or 0.0 when
+    /// charging with a key or button.
+    pub trigger_pull: f32,
+    pub show_debug: bool,
+    pub input: Input,
+    pub bindings_path: PathBuf,
+    /// The game is stopped and the pause menu shown.
+    pub paused: bool,
+    /// Open while the player changes the key bindings from the pause menu.
+    pub rebinding: Option<RebindScreen>,
     pub phase: GamePhase,
-    pub score: u32,
+    pub previous_phase: GamePhase,
+    pub timestep: FixedTimestep,
+    pub hotseat: HotSeat,
+    /// Players of the next game. Edited before the first throw and after
+    /// game over.
+    pub player_names: Vec<String>,
+    pub high_scores: HighScores,
+    pub high_scores_path: PathBuf,
+    /// Rounds of the last game that made the high-score table.
+    pub new_high_scores: Vec<HighScore>,
     pub textures_loaded: bool,
 }
 
-impl GameLoop for KlapiGame {
-    fn new(_es: &mut EngineState) -> Self {
-        let mut camera = main_camera_mut();
-        camera.zoom = 7.5;
-        camera.center = vec2(0.0, 0.0);
-        Self {
-            score: 0,
-            textures_loaded: false,
-            phase: GamePhase::Start(new_arm(vec2(-1.9, -0.45))),
+impl KlapiGame {
+    /// Switches to `phase` without interpolating from the old one.
+    fn set_phase(&mut self, phase: GamePhase) {
+        self.previous_phase = phase.clone();
+        self.phase = phase;
+    }
+
+    /// A fresh arm holding the projectile of the current player's next
+    /// throw.
+    fn arm_in_hand(&self) -> Arm {
+        let throw = self.hotseat.current_round().throws.len();
+        return new_arm(self.level.arm_start).holding(self.catalogue.for_throw(throw));
+    }
+
+    fn toggle_editor(&mut self) {
+        self.editor = match self.editor {
+            Some(_) => None,
+            None => Some(Editor::new(self.level_path.clone())),
+        };
+    }
+
+    fn update_editor(&mut self) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        match editor.update(&mut self.level) {
+            EditorAction::TestThrow => {
+                let phase = launch(self.phase.arm(), 0.0);
+                self.set_phase(phase);
+            }
+            EditorAction::ArmMoved => {
+                self.set_phase(GamePhase::Start(self.arm_in_hand()));
+            }
+            EditorAction::None => {}
         }
     }
 
-    fn update(&mut self, context: &mut EngineContext) {
-        if !self.textures_loaded {
-            load_textures(context);
-            self.textures_loaded = true;
+    /// Records the result of a throw and shows it. Test throws from the
+    /// editor are not recorded.
+    fn end_throw(&mut self, arm: &Arm, flight: Flight, result: ThrowResult) -> GamePhase {
+        if self.editor.is_some() {
+            return GamePhase::Start(self.arm_in_hand());
         }
-        draw_statics(vec2(0.0, 0.0));
-        let time_delta = delta();
-        self.phase = match &self.phase {
-            GamePhase::Start(arm) => {
-                draw_arm(arm);
-                if is_key_pressed(KeyCode::Space) {
-                    let mut new_arm = arm.clone();
-                    new_arm.speed = -60.0;
-                    GamePhase::Charging(new_arm)
+        self.hotseat.record(result, &flight, &self.level.kiuas.goal);
+        GamePhase::Results(arm.clone(), flight, 0.0)
+    }
+
+    /// Passes the turn after a throw, or ends the game when every player
+    /// is done.
+    fn next_throw(&mut self) -> GamePhase {
+        self.hotseat.pass_turn();
+        let arm = self.arm_in_hand();
+        if self.hotseat.is_over() {
+            self.record_high_scores();
+            GamePhase::GameOver(arm)
+        } else if self.hotseat.players.len() > 1 {
+            GamePhase::NextTurn(arm)
+        } else {
+            GamePhase::Start(arm)
+        }
+    }
+
+    fn record_high_scores(&mut self) {
+        let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+        self.new_high_scores.clear();
+        for player in &self.hotseat.players {
+            let score = HighScore {
+                name: player.name.clone(),
+                level: self.level.name.clone(),
+                mode: mode_name(self.preview, self.controls),
+                score: player.round.score(),
+                throws: player.round.throws.len() as u32,
+                date: date.clone(),
+            };
+            if self.high_scores.add(score.clone()).is_some() {
+                self.new_high_scores.push(score);
+            }
+        }
+        self.high_scores.last_players = self
+            .hotseat
+            .players
+            .iter()
+            .map(|player| player.name.clone())
+            .collect();
+        if let Err(error) = self.high_scores.save(&self.high_scores_path) {
+            eprintln!("Could not save high scores: {}", error);
+        }
+    }
+
+    fn new_game(&mut self) {
+        self.hotseat = HotSeat::new(self.player_names.clone(), ROUND_LENGTH);
+        self.new_high_scores.clear();
+    }
+
+    /// Players can be changed before anyone has thrown and once the game is
+    /// over.
+    fn can_edit_players(&self) -> bool {
+        let started = self
+            .hotseat
+            .players
+            .iter()
+            .any(|player| !player.round.throws.is_empty());
+        return match self.phase {
+            GamePhase::Start(_) => !started,
+            GamePhase::GameOver(_) => true,
+            _ => false,
+        };
+    }
+
+    fn launch_acceleration(&self) -> f32 {
+        match self.controls {
+            ControlScheme::AimAndPower => aim::launch_acceleration(self.power),
+            ControlScheme::Timing if self.trigger_pull > 0.0 => {
+                aim::launch_acceleration(self.trigger_pull)
+            }
+            ControlScheme::Timing | ControlScheme::Drag => LAUNCH_ACCELERATION,
+        }
+    }
+
+    /// Grabs the klapi in hand when it is clicked.
+    fn grab(&self, arm: &Arm) -> Option<GamePhase> {
+        let mouse = mouse_world();
+        let hand = arm.launch_klapi().rect.position;
+        let grabbed = is_mouse_button_pressed(MouseButton::Left)
+            && !egui().is_pointer_over_area()
+            && mouse.distance(hand) <= GRAB_RADIUS;
+        return grabbed.then(|| GamePhase::Dragging(arm.clone(), mouse));
+    }
+
+    /// Poses the arm after the drag, and throws or puts the klapi back when
+    /// it is let go.
+    fn drag(&self, arm: &Arm, grabbed_at: Vec2) -> GamePhase {
+        let pull = grabbed_at - mouse_world();
+        let dragged = drag_arm(arm, pull);
+        if is_mouse_button_down(MouseButton::Left) {
+            GamePhase::Dragging(dragged, grabbed_at)
+        } else if pull.length() >= MIN_DRAG_LENGTH {
+            launch(&dragged, self.topspin())
+        } else {
+            GamePhase::Start(self.arm_in_hand())
+        }
+    }
+
+    /// Wind the klapi meets now: at the klapi while it flies, otherwise at
+    /// the hand as it is let go.
+    fn wind(&self) -> Vec2 {
+        return match self.phase.flight() {
+            Some(flight) => self.level.wind.at(flight.klapi.rect.position, flight.time),
+            None => self.level.wind.at(self.phase.arm().hand_position(), 0.0),
+        };
+    }
+
+    /// Spin the player holds for the release, from -1.0 for full backspin
+    /// to 1.0 for full topspin.
+    fn topspin(&self) -> f32 {
+        return self.input.value(Action::Topspin) - self.input.value(Action::Backspin);
+    }
+
+    /// Turns the aim while it is held up or down, before the throw starts.
+    fn adjust_aim(&mut self) {
+        let direction = self.input.value(Action::AimUp) - self.input.value(Action::AimDown);
+        self.aim_angle =
+            (self.aim_angle + direction * AIM_SPEED * delta()).clamp(MIN_AIM_ANGLE, MAX_AIM_ANGLE);
+    }
+
+    /// A trigger starts the swing when it is let go, keys and buttons when
+    /// pressed again.
+    fn swing_requested(&self) -> bool {
+        let actions = &self.input.actions;
+        if actions.is_analog(Action::Throw) {
+            return actions.just_released(Action::Throw);
+        }
+        return actions.just_pressed(Action::Throw);
+    }
+
+    /// Phase change requested by the player during this frame.
+    fn handle_input(&mut self) -> Option<GamePhase> {
+        let timing = self.controls == ControlScheme::Timing;
+        let throw = self.input.just_pressed(Action::Throw);
+        if let GamePhase::Charging(_) = self.phase {
+            if self.input.actions.is_analog(Action::Throw) {
+                self.trigger_pull = self.trigger_pull.max(self.input.value(Action::Throw));
+            }
+        }
+        if self.controls == ControlScheme::AimAndPower && matches!(self.phase, GamePhase::Start(_))
+        {
+            self.adjust_aim();
+        }
+        match &self.phase {
+            GamePhase::Start(arm) if self.controls == ControlScheme::Drag => self.grab(arm),
+            GamePhase::Dragging(arm, grabbed_at) => Some(self.drag(arm, *grabbed_at)),
+            GamePhase::Start(arm) if throw => {
+                if timing {
+                    self.trigger_pull = 0.0;
+                    Some(charge(arm))
                 } else {
-                    GamePhase::Start(arm.clone())
+                    Some(GamePhase::Powering(arm.clone(), PowerMeter::default()))
                 }
             }
+            GamePhase::Powering(arm, meter) if throw => {
+                self.power = meter.value();
+                Some(charge(&aim_arm(arm, self.aim_angle, self.power)))
+            }
+            GamePhase::Charging(arm) if timing && self.swing_requested() => {
+                Some(start_launching(arm, self.launch_acceleration()))
+            }
+            GamePhase::Launching(arm) if timing && throw => Some(launch(arm, self.topspin())),
+            GamePhase::Launched(arm, flight) if self.input.just_pressed(Action::Reset) => {
+                let (arm, flight) = (arm.clone(), flight.clone());
+                Some(self.end_throw(&arm, flight, ThrowResult::Abandoned))
+            }
+            GamePhase::Results(_, _, _) if throw => Some(self.next_throw()),
+            GamePhase::NextTurn(arm) if throw => Some(GamePhase::Start(arm.clone())),
+            GamePhase::GameOver(_) if throw => {
+                self.new_game();
+                Some(GamePhase::Start(self.arm_in_hand()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Handles the controls and advances the physics for this frame.
+    fn update_game(&mut self) {
+        if self.input.just_pressed(Action::ToggleEditor) {
+            self.toggle_editor();
+        }
+        if self.input.just_pressed(Action::ToggleDebug) {
+            self.show_debug = !self.show_debug;
+        }
+        if self.input.just_pressed(Action::TogglePreview) {
+            self.preview = self.preview.next();
+        }
+        let between_throws = matches!(self.phase, GamePhase::Start(_));
+        if self.input.just_pressed(Action::SwitchControls) && between_throws {
+            self.controls = self.controls.next();
+        }
+        if self.editor.is_some() {
+            self.update_editor();
+        } else if let Some(phase) = self.handle_input() {
+            self.set_phase(phase);
+        }
+        let bounces = self.phase.flight().map_or(0, |flight| flight.bounces);
+        let steps = self.timestep.advance(delta());
+        for _ in 0..steps {
+            self.previous_phase = self.phase.clone();
+            self.phase = self.step(self.timestep.step);
+        }
+        if let Some(flight) = self.phase.flight() {
+            if flight.bounces > bounces {
+                effects::play_impact(flight);
+            }
+        }
+    }
+
+    /// Resumes or opens the rebinding screen. The game stays paused while
+    /// the bindings change, so the keys pressed for them do nothing else.
+    fn update_pause_menu(&mut self) {
+        if let Some(screen) = &mut self.rebinding {
+            if !screen.update(&mut self.input, &self.bindings_path) {
+                self.rebinding = None;
+            }
+            return;
+        }
+        if self.input.just_pressed(Action::Pause) {
+            self.paused = false;
+            return;
+        }
+        match hud::draw_pause(&self.input.map) {
+            PauseAction::Resume => self.paused = false,
+            PauseAction::Rebind => self.rebinding = Some(RebindScreen::new()),
+            PauseAction::None => {}
+        }
+    }
+
+    /// Advances the current phase by one fixed physics step.
+    fn step(&mut self, time_delta: f32) -> GamePhase {
+        match &self.phase {
+            GamePhase::Start(_)
+            | GamePhase::Dragging(_, _)
+            | GamePhase::NextTurn(_)
+            | GamePhase::GameOver(_) => self.phase.clone(),
+            GamePhase::Powering(arm, meter) => {
+                GamePhase::Powering(arm.clone(), meter.update(time_delta))
+            }
             GamePhase::Charging(arm) => {
-                draw_arm(arm);
-                if is_key_pressed(KeyCode::Space) || arm.angle <= arm.min_angle {
-                    GamePhase::Launching(Arm {
-                        speed: 0.0,
-                        acceleration: 720.0,
-                        ..arm.clone()
-                    })
+                if arm.shoulder.angle <= arm.shoulder.min_angle {
+                    start_launching(arm, self.launch_acceleration())
                 } else {
                     GamePhase::Charging(arm.update(time_delta))
                 }
             }
             GamePhase::Launching(arm) => {
-                draw_arm(arm);
-                if is_key_pressed(KeyCode::Space) || arm.angle >= arm.max_angle {
-                    let klapi = arm.launch_klapi();
-                    GamePhase::Launched(
-                        arm.clone(),
-                        klapi,
-                        Kiuas {
-                            barriers: vec![
-                                Barrier {
-                                    bounciness: 0.8,
-                                    rect: Rectangle {
-                                        position: vec2(3.3, -0.0),
-                                        width: 1.0,
-                                        height: 0.8,
-                                        rotation: 0.0,
-                                    },
-                                },
-                                Barrier {
-                                    bounciness: 0.2,
-                                    rect: Rectangle {
-                                        position: vec2(3.05, -0.9),
-                                        width: 0.4,
-                                        height: 0.2,
-                                        rotation: 0.0,
-                                    },
-                                },
-                            ],
-                            goal: Rectangle {
-                                position: vec2(3.1, -0.6),
-                                width: 0.3,
-                                height: 0.3,
-                                rotation: 0.0,
-                            },
-                        },
-                        vec![Barrier {
-                            bounciness: 0.5,
-                            rect: Rectangle {
-                                position: vec2(0.0, -1.40),
-                                width: 20.0,
-                                height: 0.2,
-                                rotation: 0.0,
-                            },
-                        }],
-                    )
+                if arm.shoulder.angle >= arm.shoulder.max_angle {
+                    launch(arm, self.topspin())
                 } else {
                     GamePhase::Launching(arm.update(time_delta))
                 }
             }
-            GamePhase::Launched(arm, klapi, kiuas, barriers) => {
-                draw_arm(arm);
-                if is_key_pressed(KeyCode::R) {
-                    GamePhase::Start(new_arm(arm.start_location.clone()))
+            GamePhase::Launched(arm, flight) => {
+                let arm = arm.clone();
+                let flight = flight.step(&self.level, time_delta);
+                match flight.result {
+                    Some(result) => self.end_throw(&arm, flight, result),
+                    None => GamePhase::Launched(arm, flight),
+                }
+            }
+            GamePhase::Results(arm, flight, shown) => {
+                if shown + time_delta >= RESULT_DISPLAY_TIME {
+                    self.next_throw()
                 } else {
-                    draw_sprite_rot(
-                        texture_id("klapi"),
-                        klapi.rect.position,
-                        WHITE,
-                        5,
-                        klapi.rect.rotation.to_radians(),
-                        vec2(klapi.rect.width, klapi.rect.height),
-                    );
-                    if klapi.rect.collide(&kiuas.goal) {
-                        println!("GOAL:{0:?}", kiuas.goal);
-                        println!("KLAPI:{0:?}", klapi.rect);
-                        self.score = self.score + 10;
-                        GamePhase::Start(new_arm(arm.start_location.clone()))
-                    } else {
-                        let mut updated_klapi = klapi.update(time_delta);
-                        let mut kiuas_barriers = kiuas.barriers.clone();
-                        let mut all_barriers = barriers.clone();
-                        all_barriers.append(&mut kiuas_barriers);
-                        for barrier in &all_barriers {
-                            if barrier.rect.collide(&updated_klapi.rect) {
-                                updated_klapi = barrier.on_collision(&updated_klapi);
-                            }
-                        }
-                        GamePhase::Launched(
-                            arm.clone(),
-                            updated_klapi,
-                            kiuas.clone(),
-                            barriers.to_vec(),
-                        )
-                    }
+                    GamePhase::Results(arm.clone(), flight.clone(), shown + time_delta)
                 }
             }
-        };
-        egui::Window::new("Score")
-            .anchor(egui::Align2::LEFT_TOP, egui::vec2(0.0, 0.0))
-            .show(egui(), |ui| {
-                ui.label(format!("SCORE: {}", self.score));
-            });
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-
-    use super::*;
-
-    #[test]
-    fn test_collide_right() {
-        let rect1 = Rectangle {
-            position: vec2(0.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-        let rect2 = Rectangle {
-            position: vec2(5.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-
-        let result = rect1.collide(&rect2);
-        assert_eq!(result, true);
-    }
-
-    #[test]
-    fn test_collide_left() {
-        let rect1 = Rectangle {
-            position: vec2(0.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-        let rect2 = Rectangle {
-            position: vec2(-5.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-
-        let result = rect1.collide(&rect2);
-        assert_eq!(result, true);
-    }
-
-    #[test]
-    fn test_collide_top() {
-        let rect1 = Rectangle {
-            position: vec2(0.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-        let rect2 = Rectangle {
-            position: vec2(0.0, 5.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-
-        let result = rect1.collide(&rect2);
-        assert_eq!(result, true);
-    }
-
-    #[test]
-    fn test_collide_bottom() {
-        let rect1 = Rectangle {
-            position: vec2(0.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-        let rect2 = Rectangle {
-            position: vec2(-5.0, 0.0),
-            width: 10.0,
-            height: 10.0,
-            rotation: 0.0,
+impl GameLoop for KlapiGame {
+    fn new(_es: &mut EngineState) -> Self {
+        let mut camera = main_camera_mut();
+        camera.zoom = 7.5;
+        camera.center = vec2(0.0, 0.0);
+        let level_path = level_path();
+        let level = load_level(&level_path);
+        let catalogue = load_catalogue();
+        let phase = GamePhase::Start(new_arm(level.arm_start).holding(catalogue.for_throw(0)));
+        let high_scores_path = high_scores_path();
+        let high_scores = load_high_scores(&high_scores_path);
+        let bindings_path = bindings_path();
+        let input = Input::new(load_bindings(&bindings_path));
+        let player_names = if high_scores.last_players.is_empty() {
+            vec![String::from("Player 1")]
+        } else {
+            high_scores.last_players.clone()
         };
-
-        let result = rect1.collide(&rect2);
-        assert_eq!(result, true);
+        Self {
+            level,
+            level_path,
+            catalogue,
+            editor: None,
+            preview: TrajectoryPreview::Short,
+            controls: ControlScheme::Timing,
+            aim_angle: 45.0,
+            power: 0.0,
+            trigger_pull: 0.0,
+            show_debug: false,
+            input,
+            bindings_path,
+            paused: false,
+            rebinding: None,
+            hotseat: HotSeat::new(player_names.clone(), ROUND_LENGTH),
+            player_names,
+            high_scores,
+            high_scores_path,
+            new_high_scores: Vec::new(),
+            textures_loaded: false,
+            previous_phase: phase.clone(),
+            phase,
+            timestep: FixedTimestep::new(PHYSICS_TIMESTEP, MAX_PHYSICS_STEPS),
+        }
     }
 
-    #[test]
-    fn test_pivot() {
-        let rect = Rectangle {
-            position: vec2(0.0, 0.0),
-            width: 5.0,
-            height: 10.0,
-            rotation: 0.0,
-        };
-        let result = rect.pivot(vec2(0.0, 0.0), 90.0);
-        assert_eq!(result.position, rect.position);
-        let result3 = rect.pivot(vec2(0.0, 5.0), 45.0);
-        assert_eq!(result3.position, vec2(3.535534, 1.464466));
-    }
-
-    #[test]
-    fn test_pivot_tiny_scale() {
-        let rect = Rectangle {
-            position: vec2(0.0, 0.0),
-            width: 0.18,
-            height: 0.80,
-            rotation: 0.0,
-        };
-        let result = rect.pivot(rect.position, 90.0);
-        assert_eq!(result.position, rect.position);
-        let result3 = rect.pivot(vec2(0.0, 0.40), 45.0);
-        assert_eq!(result3.position, vec2(0.282843, 0.117157));
-    }
-
-    #[test]
-    fn test_bug_in_goal_collision() {
-        let goal = Rectangle {
-            position: vec2(2.0, -1.3),
-            width: 0.3,
-            height: 0.2,
-            rotation: 0.0,
-        };
-        let klapi = Rectangle {
-            position: vec2(5.933142, -1.0068376),
-            width: 0.45,
-            height: 0.15,
-            rotation: 38.309246,
-        };
-        assert_eq!(goal.collide(&klapi), false);
-        assert_eq!(klapi.collide(&goal), false);
+    fn update(&mut self, context: &mut EngineContext) {
+        if !self.textures_loaded {
+            load_textures(context);
+            effects::load_sounds();
+            self.textures_loaded = true;
+        }
+        draw_statics(&self.level);
+        self.input.poll();
+        if self.paused {
+            self.update_pause_menu();
+        } else if self.input.just_pressed(Action::Pause) {
+            self.paused = true;
+        } else {
+            self.update_game();
+        }
+        draw_phase(&self.previous_phase, &self.phase, self.timestep.alpha());
+        if let GamePhase::Charging(arm) | GamePhase::Launching(arm) | GamePhase::Dragging(arm, _) =
+            &self.phase
+        {
+            draw_trajectory(&predict_trajectory(
+                arm,
+                &self.level,
+                self.preview,
+                self.topspin(),
+            ));
+        }
+        if self.show_debug {
+            draw_debug_overlay(&self.phase, &self.level);
+        }
+        let throw = self.hotseat.current_round().throws.len();
+        hud::draw_score(
+            &self.hotseat,
+            self.preview,
+            self.controls,
+            &self.input.map,
+            &self.catalogue.for_throw(throw),
+            &self.catalogue.for_throw(throw + 1),
+        );
+        hud::draw_high_scores(
+            &self
+                .high_scores
+                .top(&self.level.name, &mode_name(self.preview, self.controls)),
+            &self.new_high_scores,
+        );
+        match (&self.phase, self.controls) {
+            (GamePhase::Start(_), ControlScheme::AimAndPower) => {
+                hud::draw_gauges(self.aim_angle, None)
+            }
+            (GamePhase::Powering(_, meter), _) => {
+                hud::draw_gauges(self.aim_angle, Some(meter.value()))
+            }
+            (_, ControlScheme::AimAndPower) => hud::draw_gauges(self.aim_angle, Some(self.power)),
+            (GamePhase::Dragging(arm, grabbed_at), _) => {
+                let pull = *grabbed_at - mouse_world();
+                hud::draw_gauges(arm.shoulder.angle, Some(drag_power(pull)));
+            }
+            _ => {}
+        }
+        if self.level.wind != Wind::Calm && self.editor.is_none() {
+            hud::draw_wind(self.wind());
+        }
+        let topspin = self.topspin();
+        if self.phase.holds_klapi() && topspin != 0.0 {
+            hud::draw_spin(topspin);
+        }
+        if self.can_edit_players() && hud::draw_players(&mut self.player_names) {
+            if let GamePhase::Start(_) = self.phase {
+                self.new_game();
+            }
+        }
+        match &self.phase {
+            GamePhase::Results(_, _, _) => hud::draw_throw_result(&self.hotseat),
+            GamePhase::NextTurn(_) => hud::draw_next_turn(&self.hotseat, &self.input.map),
+            GamePhase::GameOver(_) => {
+                hud::draw_game_over(&self.hotseat, &self.new_high_scores, &self.input.map)
+            }
+            _ => {}
+        }
     }
 }
@@ -0,0 +1,154 @@
+use std::path::Path;
+
+use comfy::*;
+use jmk_klapit_core::input::{Action, Control, InputMap};
+
+use crate::input::Input;
+
+fn action_list(actions: &[Action]) -> String {
+    actions
+        .iter()
+        .map(|action| format!("{:?}", action))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Screen for changing which keys and buttons trigger each action. Every
+/// change is saved right away.
+pub struct RebindScreen {
+    /// Action the next key or button pressed is bound to.
+    capturing: Option<Action>,
+    /// A captured control that is already bound to other actions, waiting
+    /// for the player to confirm moving it.
+    pending: Option<(Action, Control, Vec<Action>)>,
+    status: String,
+}
+
+impl RebindScreen {
+    pub fn new() -> RebindScreen {
+        RebindScreen {
+            capturing: None,
+            pending: None,
+            status: String::new(),
+        }
+    }
+
+    fn bind(&mut self, input: &mut Input, action: Action, control: Control, path: &Path) {
+        self.status = format!("{} now triggers {:?}", control, action);
+        input.map.bind(action, control);
+        self.save(&input.map, path);
+    }
+
+    fn save(&mut self, map: &InputMap, path: &Path) {
+        if let Err(error) = map.save(path) {
+            self.status = format!("Could not save controls: {}", error);
+        }
+    }
+
+    /// Shows the screen. Returns whether it is still open.
+    pub fn update(&mut self, input: &mut Input, path: &Path) -> bool {
+        if let Some(action) = self.capturing {
+            if let Some(control) = input.captured() {
+                self.capturing = None;
+                let conflicts = input.map.conflicts_with(action, &control);
+                if conflicts.is_empty() {
+                    self.bind(input, action, control, path);
+                } else {
+                    self.pending = Some((action, control, conflicts));
+                }
+            }
+        }
+        let mut open = true;
+        egui::Window::new("Controls")
+            .anchor(egui::Align2::CENTER_CENTER, egui::vec2(0.0, 0.0))
+            .collapsible(false)
+            .show(egui(), |ui| {
+                egui::Grid::new("bindings").show(ui, |ui| {
+                    for action in Action::ALL {
+                        ui.label(format!("{:?}", action));
+                        ui.horizontal(|ui| {
+                            let controls: Vec<Control> =
+                                input.map.controls(action).cloned().collect();
+                            for control in controls {
+                                let removable = action != Action::Pause
+                                    || !input.map.is_last_pause_control(&control);
+                                let button = ui
+                                    .add_enabled(removable, egui::Button::new(control.to_string()))
+                                    .on_hover_text("Click to remove")
+                                    .on_disabled_hover_text(
+                                        "Pause opens this screen, so it keeps one control",
+                                    );
+                                if button.clicked() {
+                                    input.map.unbind(action, &control);
+                                    self.save(&input.map, path);
+                                }
+                            }
+                            if self.capturing == Some(action) {
+                                ui.label("Press a key or button...");
+                                if ui.button("Cancel").clicked() {
+                                    self.capturing = None;
+                                }
+                            } else {
+                                let add = ui.button("Add");
+                                if add.clicked() {
+                                    // Space or Enter would otherwise click the
+                                    // focused button again when captured.
+                                    add.surrender_focus();
+                                    self.capturing = Some(action);
+                                    self.pending = None;
+                                }
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+                if let Some((action, control, conflicts)) = self.pending.clone() {
+                    ui.separator();
+                    ui.label(format!(
+                        "{} already triggers {}. Move it to {:?}?",
+                        control,
+                        action_list(&conflicts),
+                        action
+                    ));
+                    let movable = !input.map.is_last_pause_control(&control);
+                    if !movable {
+                        ui.label("It is the only control of Pause, so Pause keeps it too.");
+                    }
+                    ui.horizontal(|ui| {
+                        let label = if movable { "Move" } else { "Add" };
+                        if ui.button(label).clicked() {
+                            self.pending = None;
+                            self.bind(input, action, control, path);
+                        }
+                        if ui.button("Keep").clicked() {
+                            self.pending = None;
+                        }
+                    });
+                }
+                ui.separator();
+                for (control, actions) in input.map.conflicts() {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!("{} triggers {}", control, action_list(&actions)),
+                    );
+                }
+                for action in input.map.unbound() {
+                    ui.colored_label(egui::Color32::RED, format!("{:?} has no controls", action));
+                }
+                if !self.status.is_empty() {
+                    ui.label(&self.status);
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Reset to defaults").clicked() {
+                        input.map = InputMap::default();
+                        self.status = String::from("Controls reset");
+                        self.save(&input.map, path);
+                    }
+                    if ui.button("Close").clicked() {
+                        open = false;
+                    }
+                });
+            });
+        return open;
+    }
+}